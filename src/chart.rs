@@ -0,0 +1,116 @@
+use iced::widget::canvas;
+
+const MARGIN: f32 = 32.0;
+
+/// Battle pass level over the current season, with the pace needed to reach the target
+#[derive(Debug, Clone)]
+pub struct ProgressChart
+{
+	pub season_start: chrono::DateTime<chrono::Utc>,
+	pub season_end: chrono::DateTime<chrono::Utc>,
+	pub goal_end: chrono::DateTime<chrono::Utc>, // Personal deadline or the season end
+	pub now: chrono::DateTime<chrono::Utc>,
+	pub target: crate::seasons::Level,
+	pub level: f64, // Including XP into the current level
+	pub history: Vec<(chrono::DateTime<chrono::Utc>, crate::seasons::Level)>, // Recorded levels this season, oldest first
+	pub levels_required_per_day: f64,
+	pub projected_finish: Option<chrono::DateTime<chrono::Utc>>,
+	pub region: crate::clock::Region, // Axis labels are in local dates
+}
+
+impl ProgressChart
+{
+	fn max_level(&self) -> f32
+	{
+		let highest_recorded = self.history.iter().map(|(_, level)| *level).max().unwrap_or(0);
+		std::cmp::max(std::cmp::max(self.target, highest_recorded), 1) as f32
+	}
+
+	/// Position of a level at a point in time inside a frame of the given size
+	fn point(&self, size: iced::Size, time: chrono::DateTime<chrono::Utc>, level: f32) -> iced::Point
+	{
+		let season_seconds = (self.season_end - self.season_start).num_seconds().max(1) as f32;
+		let elapsed = ((time - self.season_start).num_seconds() as f32 / season_seconds).clamp(0.0, 1.0);
+		let height = (level / self.max_level()).clamp(0.0, 1.0);
+		iced::Point::new
+		(
+			MARGIN + elapsed * (size.width - 2.0 * MARGIN),
+			size.height - MARGIN - height * (size.height - 2.0 * MARGIN),
+		)
+	}
+}
+
+impl<Message> canvas::Program<Message> for ProgressChart
+{
+	type State = ();
+
+	fn draw(&self, _state: &(), theme: &iced::Theme, bounds: iced::Rectangle, _cursor: canvas::Cursor) -> Vec<canvas::Geometry>
+	{
+		let palette = theme.palette();
+		let mut frame = canvas::Frame::new(bounds.size());
+		let size = frame.size();
+		let faded = |color: iced::Color| iced::Color { a: 0.4, ..color };
+
+		// Axes
+		let origin = self.point(size, self.season_start, 0.0);
+		let axes = canvas::Path::new(|path|
+		{
+			path.move_to(iced::Point::new(origin.x, MARGIN));
+			path.line_to(origin);
+			path.line_to(iced::Point::new(size.width - MARGIN, origin.y));
+		});
+		frame.stroke(&axes, canvas::Stroke::default().with_color(faded(palette.text)));
+		let label = |content: String, position: iced::Point, horizontal_alignment| canvas::Text
+		{
+			content,
+			position,
+			color: palette.text,
+			size: 14.0,
+			horizontal_alignment,
+			vertical_alignment: iced::alignment::Vertical::Center,
+			..canvas::Text::default()
+		};
+		frame.fill_text(label(self.max_level().to_string(), iced::Point::new(MARGIN - 4.0, MARGIN), iced::alignment::Horizontal::Right));
+		frame.fill_text(label(self.region.format(self.season_start, "%b %d"), iced::Point::new(origin.x, size.height - MARGIN / 2.0), iced::alignment::Horizontal::Left));
+		frame.fill_text(label(self.region.format(self.season_end, "%b %d"), iced::Point::new(size.width - MARGIN, size.height - MARGIN / 2.0), iced::alignment::Horizontal::Right));
+
+		// Ideal pace from the start of the season to the target
+		let ideal_pace = canvas::Path::line(origin, self.point(size, self.season_end, self.target as f32));
+		frame.stroke(&ideal_pace, canvas::Stroke
+		{
+			line_dash: canvas::LineDash { segments: &[6.0, 6.0], offset: 0 },
+			..canvas::Stroke::default().with_color(faded(palette.text)).with_width(2.0)
+		});
+
+		// Pace still needed from today
+		let remaining_days = (self.goal_end - self.now).num_seconds() as f64 / 86400.0;
+		let required_end_level = self.level + self.levels_required_per_day * remaining_days.max(0.0);
+		let required_pace = canvas::Path::line(self.point(size, self.now, self.level as f32), self.point(size, self.goal_end, required_end_level as f32));
+		frame.stroke(&required_pace, canvas::Stroke::default().with_color(palette.danger).with_width(2.0));
+
+		// Recorded levels
+		let actual = canvas::Path::new(|path|
+		{
+			path.move_to(origin);
+			for (time, level) in &self.history
+			{
+				path.line_to(self.point(size, *time, *level as f32));
+			}
+			path.line_to(self.point(size, self.now, self.level as f32));
+		});
+		frame.stroke(&actual, canvas::Stroke::default().with_color(palette.primary).with_width(3.0));
+
+		// Where the recent rate leads
+		if let Some(projected_finish) = self.projected_finish
+		{
+			let projection = canvas::Path::line(self.point(size, self.now, self.level as f32), self.point(size, projected_finish, self.target as f32));
+			frame.stroke(&projection, canvas::Stroke
+			{
+				line_dash: canvas::LineDash { segments: &[2.0, 4.0], offset: 0 },
+				..canvas::Stroke::default().with_color(palette.success).with_width(2.0)
+			});
+		}
+
+		vec![frame.into_geometry()]
+	}
+}
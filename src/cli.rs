@@ -0,0 +1,227 @@
+use crate::seasons;
+
+pub const USAGE: &str = "Usage: overhelper [status | set-level <level> | add <levels> | set-target prestige|mythic|<level>]
+Without a command the window opens";
+
+/// Things that can be done from a terminal without opening the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command
+{
+	Status,
+	SetLevel(seasons::Level),
+	Add(seasons::Level),
+	SetTarget(seasons::BattlePassTarget),
+}
+
+/// Reads the command from the arguments after the program name, None when the window should open
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String>
+{
+	let level = |value: Option<&String>, what: &str| -> Result<seasons::Level, String>
+	{
+		let value = value.ok_or_else(|| format!("Missing {}", what))?;
+		value.parse::<seasons::Level>().map_err(|_| format!("{} is not a valid {}", value, what))
+	};
+	let args: Vec<&String> = args.iter().collect();
+	let command = match args.first().map(|command| command.as_str())
+	{
+		None => return Ok(None),
+		Some("status") => Command::Status,
+		Some("set-level") => Command::SetLevel(level(args.get(1).copied(), "level")?),
+		Some("add") => Command::Add(level(args.get(1).copied(), "number of levels")?),
+		Some("set-target") => Command::SetTarget(match args.get(1).map(|target| target.as_str())
+		{
+			Some("prestige") => seasons::BattlePassTarget::Prestige,
+			Some("mythic") => seasons::BattlePassTarget::Mythic,
+			_ => seasons::BattlePassTarget::custom(level(args.get(1).copied(), "target")?).map_err(|error| error.to_string())?,
+		}),
+		Some(command) => return Err(format!("Unknown command {}", command)),
+	};
+	let expected_args = if command == Command::Status { 1 } else { 2 };
+	if args.len() > expected_args
+	{
+		return Err(format!("Unexpected argument {}", args[expected_args]));
+	}
+	Ok(Some(command))
+}
+
+/// Runs a command on the active profile, saving it when something changed
+pub fn run(command: Command, clock: std::sync::Arc<dyn crate::clock::Clock>) -> Result<(), crate::settings::SettingsError>
+{
+	if let Some(error) = seasons::SEASON_CALENDAR.override_error()
+	{
+		eprintln!("{}", error);
+	}
+	// Status only looks, so it neither migrates old files nor creates the profile directory
+	let (profiles, profile_dir) = if command == Command::Status
+	{
+		let profiles = crate::profiles::Profiles::read()?;
+		let profile_dir = crate::profiles::get_profiles_dir()?.join(&profiles.active);
+		(profiles, profile_dir)
+	}
+	else
+	{
+		let profiles = crate::profiles::Profiles::load()?;
+		let profile_dir = crate::profiles::get_profile_dir(&profiles.active)?;
+		(profiles, profile_dir)
+	};
+	for line in run_in(command, &profile_dir, profiles, clock)?
+	{
+		println!("{}", line);
+	}
+	Ok(())
+}
+
+/// Runs a command on the profile stored in `profile_dir`, returning what to print
+fn run_in(command: Command, profile_dir: &std::path::Path, profiles: crate::profiles::Profiles, clock: std::sync::Arc<dyn crate::clock::Clock>) -> Result<Vec<String>, crate::settings::SettingsError>
+{
+	let settings_path = profile_dir.join(crate::settings::SETTINGS_FILE_NAME);
+	let history_path = profile_dir.join(crate::history::HISTORY_FILE_NAME);
+	let (loaded_settings, loaded_history) = if command == Command::Status
+	{
+		// Only looking, so corrupt files are reported and left where they are
+		((crate::settings::read_settings_file(&settings_path)?, None), (crate::history::load_history_file(&history_path)?, None))
+	}
+	else
+	{
+		(crate::settings::load_settings_file_or_default(&settings_path), crate::history::load_history_file_or_default(&history_path))
+	};
+	let mut app = crate::settings::settings_to_appstate(&crate::settings::OverHelperSettings::default(), clock);
+	app.use_profile(profiles, loaded_settings, loaded_history);
+	if let Some(notice) = &app.settings_notice
+	{
+		eprintln!("{}", notice.message);
+	}
+
+	let season = seasons::get_current_season(app.clock.as_ref());
+	match command
+	{
+		Command::Status => (),
+		Command::SetLevel(level) => app.battle_pass = app.battle_pass.with_level(level),
+		Command::Add(levels) => app.battle_pass = app.battle_pass.with_level(app.battle_pass.level().saturating_add(levels)),
+		Command::SetTarget(target) =>
+		{
+			app.battle_pass_target = target;
+			app.battle_pass = app.battle_pass.with_target(target.level(&season));
+		},
+	}
+	if command != Command::Status
+	{
+		app.record_level(); // Includes a level clamped by a lower target
+	}
+	let mut lines = status(&app);
+	if command != Command::Status
+	{
+		crate::settings::write_settings_file(&settings_path, &crate::settings::appstate_to_settings(&app))?;
+		crate::history::write_history_file(&history_path, &app.history)?;
+		if !crate::settings::SAVES_FILES
+		{
+			lines.push(String::from("Debug builds don't save, nothing was written"));
+		}
+	}
+	Ok(lines)
+}
+
+/// The figures from the top of the tracker, one per line
+fn status(app: &crate::OverHelperApp) -> Vec<String>
+{
+	let clock = app.clock.as_ref();
+	let season = seasons::get_current_season(clock);
+	let progress = app.battle_pass;
+	let mut lines = vec![format!("Profile {}", app.profiles.active)];
+	lines.push(format!("{} remaining in {}, which ends {}", seasons::Countdown::from(seasons::get_remaining_time(clock, None)), season, app.region.format(season.end, "%B %d %H:%M")));
+	let goal_end = seasons::get_goal_end(clock, app.battle_pass_deadline);
+	if goal_end != season.end
+	{
+		lines.push(format!("{} remaining until your deadline on {}", seasons::Countdown::from(seasons::get_remaining_time(clock, app.battle_pass_deadline)), app.region.format(goal_end, "%B %d %H:%M")));
+	}
+	let target = match app.battle_pass_target
+	{
+		seasons::BattlePassTarget::Custom { .. } => format!("level {}", progress.target()),
+		target => format!("{} (level {})", target, progress.target()),
+	};
+	lines.push(format!("Level {} and {} XP, aiming for {}", progress.level(), progress.xp(), target));
+	if progress.is_complete()
+	{
+		lines.push(String::from(seasons::TARGET_REACHED));
+		return lines;
+	}
+	let xp_required_per_day = seasons::get_xp_required_per_day(progress, clock, app.battle_pass_deadline);
+	lines.push(format!("{} days left, {:.0} XP ({:.2} levels) per day needed", seasons::get_remaining_days(clock, app.battle_pass_deadline, app.region), xp_required_per_day.ceil(), xp_required_per_day / seasons::XP_PER_LEVEL as f64));
+	match seasons::get_xp_required_per_play_day(progress, &app.play_schedule, clock, app.battle_pass_deadline, app.region)
+	{
+		Some(xp) => lines.push(format!("{:.2} levels per play session", xp / seasons::XP_PER_LEVEL as f64)),
+		None => lines.push(String::from("No play days left in your schedule")),
+	}
+	lines
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn parse(args: &str) -> Result<Option<Command>, String>
+	{
+		parse_args(&args.split_whitespace().map(String::from).collect::<Vec<_>>())
+	}
+
+	#[test]
+	fn commands_are_parsed()
+	{
+		assert_eq!(parse(""), Ok(None));
+		assert_eq!(parse("status"), Ok(Some(Command::Status)));
+		assert_eq!(parse("set-level 47"), Ok(Some(Command::SetLevel(47))));
+		assert_eq!(parse("add 2"), Ok(Some(Command::Add(2))));
+		assert_eq!(parse("set-target prestige"), Ok(Some(Command::SetTarget(seasons::BattlePassTarget::Prestige))));
+		assert_eq!(parse("set-target mythic"), Ok(Some(Command::SetTarget(seasons::BattlePassTarget::Mythic))));
+		assert_eq!(parse("set-target 120"), Ok(Some(Command::SetTarget(seasons::BattlePassTarget::Custom { level: 120 }))));
+	}
+
+	#[test]
+	fn bad_commands_are_rejected()
+	{
+		assert!(parse("levelup").is_err());
+		assert!(parse("set-level").is_err());
+		assert!(parse("set-level -3").is_err());
+		assert!(parse("add two").is_err());
+		assert!(parse("set-target 0").is_err());
+		assert!(parse("set-target 201").is_err());
+		assert!(parse("set-target legendary").is_err());
+		assert!(parse("status now").is_err());
+		assert!(parse("add 2 3").is_err());
+	}
+
+	#[test]
+	fn commands_run_on_a_profile_directory()
+	{
+		use chrono::TimeZone;
+
+		let dir = crate::settings::test_dir("cli");
+		let settings_path = dir.join(crate::settings::SETTINGS_FILE_NAME);
+		let settings = r#"{ "version": 2, "battle_pass": { "level": 40, "season": 2 } }"#;
+		std::fs::write(&settings_path, settings).unwrap();
+		// Saturday in season 2, which ends on Tuesday February 7 at 18:00
+		let clock: std::sync::Arc<dyn crate::clock::Clock> = std::sync::Arc::new(crate::clock::FixedClock(chrono::Utc.with_ymd_and_hms(2023, 2, 4, 12, 0, 0).unwrap()));
+		let run = |command| run_in(command, &dir, crate::profiles::Profiles::default(), clock.clone());
+
+		let lines = run(Command::Add(2)).unwrap();
+		assert_eq!(lines[0], "Profile Default");
+		assert!(lines[1].starts_with("3d 6h 0m remaining in Season 2"), "{}", lines[1]);
+		assert_eq!(lines[2], "Level 42 and 0 XP, aiming for All Prestige Titles (level 200)");
+		if crate::settings::SAVES_FILES
+		{
+			assert_eq!(crate::settings::read_settings_file(&settings_path).unwrap().battle_pass.level, 42);
+		}
+		else
+		{
+			assert_eq!(lines.last().unwrap(), "Debug builds don't save, nothing was written");
+			assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), settings);
+		}
+
+		// Status leaves a corrupt file alone
+		std::fs::write(&settings_path, "{ \"version\": 2, ").unwrap();
+		assert!(matches!(run(Command::Status), Err(crate::settings::SettingsError::Parse { .. })));
+		assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), "{ \"version\": 2, ");
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
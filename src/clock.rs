@@ -0,0 +1,120 @@
+/// Source of the current time, so season maths doesn't depend on the wall clock
+pub trait Clock: std::fmt::Debug + Send + Sync
+{
+	fn now(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+/// The real time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock
+{
+	fn now(&self) -> chrono::DateTime<chrono::Utc>
+	{
+		chrono::Utc::now()
+	}
+}
+
+/// Always returns the same time, for tests and what-if calculations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub chrono::DateTime<chrono::Utc>);
+
+impl Clock for FixedClock
+{
+	fn now(&self) -> chrono::DateTime<chrono::Utc>
+	{
+		self.0
+	}
+}
+
+/// Timezone that calendar days are counted in, the system one unless the user picked a region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "Option<String>", into = "Option<String>")]
+pub enum Region
+{
+	#[default]
+	System,
+	Zone(chrono_tz::Tz),
+}
+
+lazy_static::lazy_static!
+{
+	/// Every region that can be picked, the system timezone first
+	pub static ref REGIONS: Vec<Region> = std::iter::once(Region::System)
+		.chain(chrono_tz::TZ_VARIANTS.iter().map(|zone| Region::Zone(*zone)))
+		.collect();
+}
+
+impl Region
+{
+	/// Calendar day `time` falls on in this region
+	pub fn local_date(self, time: chrono::DateTime<chrono::Utc>) -> chrono::NaiveDate
+	{
+		match self
+		{
+			Region::System => time.with_timezone(&chrono::Local).date_naive(),
+			Region::Zone(zone) => time.with_timezone(&zone).date_naive(),
+		}
+	}
+
+	/// Moment the calendar day starts in this region
+	pub fn start_of_day(self, date: chrono::NaiveDate) -> chrono::DateTime<chrono::Utc>
+	{
+		use chrono::TimeZone;
+		let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+		let start = match self
+		{
+			Region::System => chrono::Local.from_local_datetime(&midnight).earliest().map(|time| time.with_timezone(&chrono::Utc)),
+			Region::Zone(zone) => zone.from_local_datetime(&midnight).earliest().map(|time| time.with_timezone(&chrono::Utc)),
+		};
+		// Midnight skipped by a DST change, close enough for counting days
+		start.unwrap_or_else(|| chrono::Utc.from_utc_datetime(&midnight))
+	}
+
+	pub fn format(self, time: chrono::DateTime<chrono::Utc>, format: &str) -> String
+	{
+		match self
+		{
+			Region::System => time.with_timezone(&chrono::Local).format(format).to_string(),
+			Region::Zone(zone) => time.with_timezone(&zone).format(format).to_string(),
+		}
+	}
+}
+
+// Unknown timezone names fall back to the system timezone instead of failing the whole settings file
+impl From<Option<String>> for Region
+{
+	fn from(name: Option<String>) -> Self
+	{
+		match name.and_then(|name| name.parse().ok())
+		{
+			Some(zone) => Region::Zone(zone),
+			None => Region::System,
+		}
+	}
+}
+
+impl From<Region> for Option<String>
+{
+	fn from(region: Region) -> Self
+	{
+		match region
+		{
+			Region::System => None,
+			Region::Zone(zone) => Some(zone.name().to_string()),
+		}
+	}
+}
+
+impl std::fmt::Display for Region
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Region::System => write!(f, "System timezone"),
+			Region::Zone(zone) => write!(f, "{}", zone.name()),
+		}
+	}
+}
@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, Region};
+use crate::seasons::Level;
+use crate::settings::SettingsError;
+
+// Level changes this close together on the same day are merged, so dragging the slider doesn't flood the log
+const MERGE_WINDOW_MINUTES: i64 = 5;
+
+/// A battle pass level at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelEntry
+{
+	pub time: chrono::DateTime<chrono::Utc>,
+	pub level: Level,
+	pub season: u8,
+}
+
+/*
+Example file:
+{
+	"entries":
+	[
+		{ "time": "2023-01-01T18:30:00Z", "level": 12, "season": 2 }
+	]
+}
+*/
+/// Every recorded level change, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelHistory
+{
+	pub entries: Vec<LevelEntry>,
+}
+
+/// Levels at the start and end of one local calendar day with at least one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaySnapshot
+{
+	pub date: chrono::NaiveDate,
+	pub season: u8,
+	pub start_level: Level, // Level at the end of the previous day, or the season's first entry on its first day
+	pub end_level: Level,
+}
+
+impl DaySnapshot
+{
+	pub fn levels_gained(&self) -> Level
+	{
+		self.end_level.saturating_sub(self.start_level)
+	}
+}
+
+impl LevelHistory
+{
+	/// Adds a level, the first one of a season is the baseline gains are counted from
+	pub fn record(&mut self, time: chrono::DateTime<chrono::Utc>, level: Level, season: u8, region: Region)
+	{
+		let count = self.entries.len();
+		// Merging into the baseline would move it, turning the first change into no gain at all
+		let last_is_baseline = count < 2 || self.entries[count - 2].season != self.entries[count - 1].season;
+		if let Some(last) = self.entries.last_mut()
+		{
+			if last.level == level && last.season == season
+			{
+				return;
+			}
+			let same_day = region.local_date(last.time) == region.local_date(time);
+			if last.season == season && same_day && !last_is_baseline && time - last.time < chrono::Duration::minutes(MERGE_WINDOW_MINUTES)
+			{
+				*last = LevelEntry { time, level, season };
+				return;
+			}
+		}
+		self.entries.push(LevelEntry { time, level, season });
+	}
+
+	/// One snapshot per local day that has entries, oldest first
+	pub fn daily_rollup(&self, region: Region) -> Vec<DaySnapshot>
+	{
+		let mut days: Vec<DaySnapshot> = Vec::new();
+		for entry in &self.entries
+		{
+			let date = region.local_date(entry.time);
+			match days.last_mut()
+			{
+				Some(day) if day.date == date && day.season == entry.season => day.end_level = entry.level,
+				previous =>
+				{
+					let start_level = match previous
+					{
+						Some(previous) if previous.season == entry.season => previous.end_level,
+						_ => entry.level, // Where the season's tracking started, not a gain
+					};
+					days.push(DaySnapshot { date, season: entry.season, start_level, end_level: entry.level });
+				},
+			}
+		}
+		days
+	}
+
+	/// Levels gained on local days from `first_day` onwards
+	pub fn levels_gained_since(&self, first_day: chrono::NaiveDate, region: Region) -> u32
+	{
+		self.daily_rollup(region).iter()
+			.filter(|day| day.date >= first_day)
+			.map(|day| day.levels_gained() as u32)
+			.sum()
+	}
+
+	pub fn levels_gained_today(&self, clock: &dyn Clock, region: Region) -> u32
+	{
+		self.levels_gained_since(region.local_date(clock.now()), region)
+	}
+
+	/// Levels gained since Monday
+	pub fn levels_gained_this_week(&self, clock: &dyn Clock, region: Region) -> u32
+	{
+		let today = region.local_date(clock.now());
+		let monday = today - chrono::Duration::days(chrono::Datelike::weekday(&today).num_days_from_monday() as i64);
+		self.levels_gained_since(monday, region)
+	}
+
+	/// Levels gained in a season on each local day from `first_day` to `last_day`, including days without play
+	pub fn daily_gains(&self, season: u8, first_day: chrono::NaiveDate, last_day: chrono::NaiveDate, region: Region) -> Vec<f64>
+	{
+		let days = self.daily_rollup(region);
+		first_day.iter_days()
+			.take_while(|date| *date <= last_day)
+			.map(|date| days.iter()
+				.filter(|day| day.date == date && day.season == season)
+				.map(|day| day.levels_gained() as f64)
+				.sum())
+			.collect()
+	}
+
+	/// Daily gains in a season from the first day it was tracked to `last_day`, days before tracking started aren't known
+	pub fn tracked_daily_gains(&self, season: u8, last_day: chrono::NaiveDate, region: Region) -> Vec<f64>
+	{
+		match self.entries.iter().find(|entry| entry.season == season)
+		{
+			Some(first) => self.daily_gains(season, region.local_date(first.time), last_day, region),
+			None => Vec::new(),
+		}
+	}
+
+	pub fn levels_gained_in_season(&self, season: u8, region: Region) -> u32
+	{
+		self.daily_rollup(region).iter()
+			.filter(|day| day.season == season)
+			.map(|day| day.levels_gained() as u32)
+			.sum()
+	}
+}
+
+pub const HISTORY_FILE_NAME: &str = "history.json";
+
+pub fn get_history_path(profile: &str) -> Result<std::path::PathBuf, SettingsError>
+{
+	Ok(crate::profiles::get_profile_dir(profile)?.join(HISTORY_FILE_NAME))
+}
+
+/// Loads the history, starting fresh with a message for the user if it can't be read.
+/// A corrupt file is moved to history.json.corrupt-<timestamp> first, so saving doesn't overwrite it
+pub fn load_history_or_default(profile: &str) -> (LevelHistory, Option<String>)
+{
+	match get_history_path(profile)
+	{
+		Ok(history_path) => load_history_file_or_default(&history_path),
+		Err(error) => (LevelHistory::default(), Some(format!("{}. Level history starts fresh", error))),
+	}
+}
+
+// Same as load_history_or_default, for a history file at a known path
+pub fn load_history_file_or_default(history_path: &std::path::Path) -> (LevelHistory, Option<String>)
+{
+	let error = match load_history_file(history_path)
+	{
+		Ok(history) => return (history, None),
+		Err(error) => error,
+	};
+	let message = match &error
+	{
+		SettingsError::Parse { path, .. } => match crate::settings::move_aside(path)
+		{
+			Ok(corrupt_path) => format!("{}. Level history starts fresh, the old file was kept as {}", error, corrupt_path.display()),
+			Err(rename_error) => format!("{}. It could not be backed up: {}", error, rename_error),
+		},
+		_ => format!("{}. Level history starts fresh", error),
+	};
+	(LevelHistory::default(), Some(message))
+}
+
+pub fn load_history_file(history_path: &std::path::Path) -> Result<LevelHistory, SettingsError>
+{
+	if !history_path.exists()
+	{
+		return Ok(LevelHistory::default());
+	}
+	let contents = std::fs::read(history_path).map_err(SettingsError::io("open", history_path))?;
+	serde_json::from_slice(&contents).map_err(|source| SettingsError::Parse { path: history_path.to_path_buf(), source })
+}
+
+pub fn write_history(profile: &str, history: &LevelHistory) -> Result<(), SettingsError>
+{
+	write_history_file(&get_history_path(profile)?, history)
+}
+
+pub fn write_history_file(history_path: &std::path::Path, history: &LevelHistory) -> Result<(), SettingsError>
+{
+	// Not written in debug mode, like the settings
+	if !crate::settings::SAVES_FILES
+	{
+		return Ok(());
+	}
+	let contents = serde_json::to_vec_pretty(history).map_err(|source| SettingsError::Serialize { path: history_path.to_path_buf(), source })?;
+	crate::settings::write_file_atomically(history_path, &contents)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use chrono::TimeZone;
+
+	const UTC: Region = Region::Zone(chrono_tz::UTC);
+
+	fn noon(day: u32) -> chrono::DateTime<chrono::Utc>
+	{
+		chrono::Utc.with_ymd_and_hms(2023, 1, day, 12, 0, 0).unwrap()
+	}
+
+	#[test]
+	fn rollup_counts_levels_per_day_and_season()
+	{
+		let mut history = LevelHistory::default();
+		history.record(noon(2), 10, 2, UTC); // Monday, level when the app was opened
+		history.record(noon(2) + chrono::Duration::minutes(1), 11, 2, UTC); // Not merged into the baseline
+		history.record(noon(2) + chrono::Duration::minutes(2), 12, 2, UTC); // Merged into the previous entry
+		history.record(noon(2) + chrono::Duration::hours(1), 13, 2, UTC);
+		history.record(noon(3), 16, 2, UTC);
+		history.record(noon(4), 15, 2, UTC); // Corrected down
+		history.record(noon(5), 0, 3, UTC); // New season
+		history.record(noon(5) + chrono::Duration::hours(1), 3, 3, UTC);
+
+		assert_eq!(history.entries.len(), 7);
+		let days = history.daily_rollup(UTC);
+		assert_eq!(days.iter().map(DaySnapshot::levels_gained).collect::<Vec<_>>(), vec![3, 3, 0, 3]);
+		assert_eq!(history.levels_gained_in_season(2, UTC), 6);
+		assert_eq!(history.levels_gained_in_season(3, UTC), 3);
+
+		let clock = crate::clock::FixedClock(noon(5));
+		assert_eq!(history.levels_gained_today(&clock, UTC), 3);
+		assert_eq!(history.levels_gained_this_week(&clock, UTC), 9);
+	}
+
+	#[test]
+	fn first_entry_is_a_baseline()
+	{
+		// A player already at 47 when tracking starts gains 1 by going up once
+		let mut history = LevelHistory::default();
+		history.record(noon(2), 47, 2, UTC);
+		history.record(noon(2) + chrono::Duration::minutes(1), 48, 2, UTC);
+		assert_eq!(history.levels_gained_in_season(2, UTC), 1);
+		assert_eq!(history.daily_gains(2, noon(1).date_naive(), noon(2).date_naive(), UTC), vec![0.0, 1.0]);
+
+		// Even a season with a single entry has gained nothing yet
+		let history = LevelHistory { entries: vec![LevelEntry { time: noon(3), level: 20, season: 2 }] };
+		assert_eq!(history.levels_gained_in_season(2, UTC), 0);
+	}
+
+	#[test]
+	fn gains_start_when_tracking_starts()
+	{
+		// Tracking started on the 4th, well into the season
+		let mut history = LevelHistory::default();
+		history.record(noon(4), 30, 2, UTC);
+		history.record(noon(5), 34, 2, UTC);
+		history.record(noon(7), 36, 2, UTC);
+		assert_eq!(history.tracked_daily_gains(2, noon(8).date_naive(), UTC), vec![0.0, 4.0, 0.0, 2.0, 0.0]);
+		assert!(history.tracked_daily_gains(3, noon(8).date_naive(), UTC).is_empty());
+	}
+
+	#[test]
+	fn corrupt_history_is_moved_aside()
+	{
+		let dir = crate::settings::test_dir("history");
+		let history_path = dir.join(HISTORY_FILE_NAME);
+		std::fs::write(&history_path, r#"{ "entries": [ { "time": "2023-01-02T12:00:00Z", "lev"#).unwrap();
+
+		let (history, message) = load_history_file_or_default(&history_path);
+		assert_eq!(history, LevelHistory::default());
+		assert!(!history_path.exists());
+		let corrupt_path = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+		assert!(corrupt_path.file_name().unwrap().to_string_lossy().starts_with("history.json.corrupt-"));
+		assert!(message.unwrap().contains(&corrupt_path.display().to_string()));
+
+		// A missing file is just an empty history
+		assert_eq!(load_history_file_or_default(&history_path), (LevelHistory::default(), None));
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -1,14 +1,43 @@
 use iced::Application;
 
+mod chart;
+mod cli;
+mod clock;
+mod history;
+mod planner;
+mod profiles;
+mod role_mastery;
 mod seasons;
 mod settings;
+mod simulator;
 
 fn main()
 {
+	let clock: std::sync::Arc<dyn clock::Clock> = std::sync::Arc::new(clock::SystemClock);
+	// Commands run in the terminal, no arguments opens the window
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	match cli::parse_args(&args)
+	{
+		Ok(None) => (),
+		Ok(Some(command)) =>
+		{
+			if let Err(error) = cli::run(command, clock)
+			{
+				eprintln!("{}", error);
+				std::process::exit(1);
+			}
+			return;
+		},
+		Err(error) =>
+		{
+			eprintln!("{}\n{}", error, cli::USAGE);
+			std::process::exit(2);
+		},
+	}
 	let settings = iced::settings::Settings
 	{
 		exit_on_close_request: false, // save on exit
-		..Default::default()
+		..iced::settings::Settings::with_flags(clock)
 	};
 	// Start the iced application
 	OverHelperApp::run(settings)
@@ -18,42 +47,93 @@ fn main()
 #[derive(Debug, Clone)]
 pub enum Message
 {
-	UpdateBattlePassLevel(u8),
+	UpdateBattlePassLevel(seasons::Level),
 	UpdateBattlePassTextInput(String),
+	UpdateBattlePassXpTextInput(String),
 
 	EventOccurred(iced_native::event::Event),
+	Tick,
+	DismissSeasonBanner,
+
+	OpenSettingsBackup,
+	RestoreSettingsBackup,
+	DismissSettingsNotice,
+
+	RoleMasteryMessage(role_mastery::Message),
+	PlannerMessage(planner::Message),
+	SimulatorMessage(simulator::Message),
+	SelectTab(Tab),
+	SwitchProfile(String),
 
 	OpenSettings,
-	ExitSettings,
+	ApplySettings,
+	CancelSettings,
 	SettingsMessage(settings::Message),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab
+{
+	Tracker,
+	Planner,
+	Simulator,
+}
+
 #[derive(Debug, Clone)]
 pub struct OverHelperApp
 {
-	pub battle_pass_level: u8,
-	pub battle_pass_target: u8, // Should be prestige or 80
-	// Roll mastery
-	pub tank_wins: u8,
-	pub damage_wins: u8,
-	pub support_wins: u8,
+	pub battle_pass: seasons::BattlePassProgress,
+	pub battle_pass_target: seasons::BattlePassTarget, // Resolved into a level for the tracked season by battle_pass
+	pub battle_pass_premium: bool,
+	pub battle_pass_deadline: Option<chrono::DateTime<chrono::Utc>>,
+	pub boost_windows: Vec<seasons::BoostWindow>,
+	pub play_schedule: seasons::PlaySchedule,
+	pub season_number: u8, // Season the battle pass level belongs to
+	pub season_archive: Vec<seasons::ArchivedSeason>,
+	pub new_season_banner: Option<u8>,
+	pub history: history::LevelHistory,
+	pub profiles: profiles::Profiles,
+	pub role_mastery: role_mastery::RoleMastery,
+	pub planner: planner::Planner,
+	pub simulator: simulator::Simulator, // Not saved, it only plays with hypotheticals
+	pub tab: Tab,
 
 	pub theme: iced::Theme,
+	pub pace_estimator: seasons::PaceEstimator,
+	pub region: clock::Region,
 
 	pub settings_page: Option<settings::SettingsPage>,
+	pub settings_notice: Option<settings::SettingsNotice>,
+	pub save_failed: bool, // Closing again quits without saving
+
+	pub clock: std::sync::Arc<dyn clock::Clock>,
 }
 
 impl Application for OverHelperApp
 {
 	type Executor = iced::executor::Default;
-	type Flags = ();
+	type Flags = std::sync::Arc<dyn clock::Clock>;
 	type Message = Message;
 	type Theme = iced::theme::Theme;
 
-	fn new(_flags: ()) -> (Self, iced::Command<Self::Message>)
+	fn new(clock: Self::Flags) -> (Self, iced::Command<Self::Message>)
 	{
+		let mut app = settings::settings_to_appstate(&settings::OverHelperSettings::default(), clock);
+		match profiles::Profiles::load()
+		{
+			Ok(profiles) => app.load_profile(profiles),
+			Err(error) =>
+			{
+				app.load_profile(profiles::Profiles::default());
+				app.add_settings_notice(format!("{}. Using the default profile", error));
+			},
+		}
+		if let Some(error) = seasons::SEASON_CALENDAR.override_error()
+		{
+			app.add_settings_notice(error.to_string());
+		}
 		(
-			settings::settings_to_appstate(&settings::load_settings_or_default()),
+			app,
 			iced::Command::none()
 		)
 	}
@@ -65,7 +145,11 @@ impl Application for OverHelperApp
 
 	fn subscription(&self) -> iced::Subscription<Self::Message>
 	{
-		iced_native::subscription::events().map(Message::EventOccurred)
+		iced::Subscription::batch
+		([
+			iced_native::subscription::events().map(Message::EventOccurred),
+			iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::Tick), // Keeps the countdown live
+		])
 	}
 
 	fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message>
@@ -74,40 +158,151 @@ impl Application for OverHelperApp
 		{
 			Message::UpdateBattlePassTextInput(new_level) =>
 			{
-				if let Ok(new_level) = new_level.parse::<u8>()
+				if let Ok(new_level) = new_level.parse::<seasons::Level>()
+				{
+					self.battle_pass = self.battle_pass.with_level(new_level);
+					self.record_level();
+				}
+			},
+			Message::UpdateBattlePassXpTextInput(new_xp) =>
+			{
+				if new_xp.is_empty()
+				{
+					self.battle_pass = self.battle_pass.with_xp(0);
+				}
+				else if let Ok(new_xp) = new_xp.parse::<u32>()
 				{
-					self.battle_pass_level = std::cmp::min(new_level, self.battle_pass_target);
+					self.battle_pass = self.battle_pass.with_xp(new_xp);
 				}
 			},
 			Message::UpdateBattlePassLevel(new_level) =>
 			{
-				self.battle_pass_level = new_level;
+				self.battle_pass = self.battle_pass.with_level(new_level);
+				self.record_level();
 			},
 			Message::EventOccurred(event) =>
 			{
-				match event
+				if let iced_native::event::Event::Window(iced_native::window::Event::CloseRequested) = event // Write settings to file on close
+				{
+					match self.save()
+					{
+						Err(error) if !self.save_failed =>
+						{
+							self.save_failed = true;
+							self.settings_notice = Some(settings::SettingsNotice
+							{
+								message: format!("{}. Close again to quit without saving", error),
+								backup_path: None,
+							});
+						},
+						_ => return iced::window::close(),
+					}
+				}
+			},
+			Message::Tick =>
+			{
+				self.check_season_rollover();
+			},
+			Message::DismissSeasonBanner =>
+			{
+				self.new_season_banner = None;
+			},
+			Message::OpenSettingsBackup =>
+			{
+				if let Some(backup_path) = self.settings_notice.as_ref().and_then(|notice| notice.backup_path.clone())
+				{
+					if let Err(error) = settings::open_in_system(&backup_path)
+					{
+						self.settings_notice = Some(settings::SettingsNotice { message: error.to_string(), backup_path: Some(backup_path) });
+					}
+				}
+			},
+			Message::RestoreSettingsBackup =>
+			{
+				if let Some(backup_path) = self.settings_notice.as_ref().and_then(|notice| notice.backup_path.clone())
 				{
-					iced_native::event::Event::Window(iced_native::window::Event::CloseRequested) => // Write settings to file on close
+					match settings::restore_settings_backup(&self.profiles.active, &backup_path)
 					{
-						let settings = settings::appstate_to_settings(&self);
-						settings::write_settings(&settings);
-						return iced::window::close();
-					},
-					_ => (),
+						Ok(settings) => self.apply_loaded_settings(&settings),
+						Err(error) => self.settings_notice = Some(settings::SettingsNotice { message: error.to_string(), backup_path: Some(backup_path) }),
+					}
 				}
 			},
-			Message::ExitSettings =>
+			Message::DismissSettingsNotice =>
+			{
+				self.settings_notice = None;
+			},
+			Message::ApplySettings =>
 			{
 				// Use the new settings
-				let settings = self.settings_page.clone().unwrap();
-				self.battle_pass_target = settings.battle_pass_custom_target;
-				self.battle_pass_level = std::cmp::min(self.battle_pass_level, self.battle_pass_target);
-				self.theme = settings.theme.into();
+				let settings = self.settings_page.take().unwrap();
+				self.battle_pass_target = settings.battle_pass_target();
+				self.battle_pass = self.battle_pass.with_target(self.battle_pass_target.level(&seasons::get_current_season(self.clock.as_ref())));
+				self.record_level(); // A lower target may have clamped the level
+				self.theme = settings.theme;
+				self.pace_estimator = settings.pace_estimator;
+				self.region = settings.region;
+				self.battle_pass_premium = settings.premium;
+				self.battle_pass_deadline = settings.deadline;
+				self.boost_windows = settings.boost_windows;
+				self.play_schedule = settings.play_schedule;
+			},
+			Message::CancelSettings =>
+			{
 				self.settings_page = None;
 			},
+			Message::RoleMasteryMessage(role_mastery_message) =>
+			{
+				self.role_mastery.update(role_mastery_message);
+			},
+			Message::PlannerMessage(planner_message) =>
+			{
+				self.planner.update(planner_message);
+			},
+			Message::SimulatorMessage(simulator_message) =>
+			{
+				self.simulator.update(simulator_message);
+			},
+			Message::SelectTab(tab) =>
+			{
+				self.tab = tab;
+			},
+			Message::SwitchProfile(name) =>
+			{
+				if name != self.profiles.active
+				{
+					// Save the profile being left, staying on it if that fails
+					match self.save()
+					{
+						Ok(()) =>
+						{
+							let profiles = profiles::Profiles { active: name, ..self.profiles.clone() };
+							self.load_profile(profiles);
+							if let Err(error) = profiles::write_active_profile(&self.profiles.active)
+							{
+								self.add_settings_notice(error.to_string());
+							}
+						},
+						Err(error) => self.settings_notice = Some(settings::SettingsNotice { message: format!("{}. Staying on profile {}", error, self.profiles.active), backup_path: None }),
+					}
+				}
+			},
 			Message::OpenSettings =>
 			{
-				self.settings_page = Some(settings::SettingsPage::new());
+				self.settings_page = Some(settings::SettingsPage::from_settings(&settings::appstate_to_settings(self), seasons::get_current_season(self.clock.as_ref()), &self.profiles));
+			},
+			Message::SettingsMessage(settings::Message::RestoreBackup(backup_path)) =>
+			{
+				match settings::restore_settings_backup(&self.profiles.active, &backup_path)
+				{
+					Ok(settings) => self.apply_loaded_settings(&settings),
+					Err(error) => self.settings_page.as_mut().unwrap().restore_error = Some(error.to_string()),
+				}
+			},
+			Message::SettingsMessage(profile_message @ (settings::Message::CreateProfile | settings::Message::RenameProfile(_) | settings::Message::ConfirmDeleteProfile(_))) =>
+			{
+				let profile_error = self.update_profiles(profile_message).err();
+				self.settings_page.as_mut().unwrap().profile_error = profile_error;
 			},
 			Message::SettingsMessage(settings_message) =>
 			{
@@ -118,49 +313,303 @@ impl Application for OverHelperApp
 	}
 
 
-	fn view(&self) -> iced::Element<Self::Message>
+	fn view(&self) -> iced::Element<'_, Self::Message>
 	{
-		if self.settings_page.is_some()
+		if let Some(settings_page) = &self.settings_page
 		{
-			let page = self.settings_page
-				.as_ref()
-				.unwrap()
+			let page = settings_page
 				.view()
 				.map(Message::SettingsMessage)
 				;
-			let page = iced::widget::Container::new(page)
+			let page = iced::widget::Container::new(iced::widget::Scrollable::new(page))
 				.width(iced::Length::Fill)
 				.height(iced::Length::FillPortion(6))
 				;
-			let back_button = iced::widget::Button::new(iced::widget::Text::new("Back"))
-				.on_press(Message::ExitSettings)
+			let cancel_button = iced::widget::Button::new(iced::widget::Text::new("Cancel"))
+				.on_press(Message::CancelSettings)
+				.width(iced::Length::FillPortion(1))
+				;
+			let apply_button = iced::widget::Button::new(iced::widget::Text::new("Apply"))
+				.on_press(Message::ApplySettings)
 				.width(iced::Length::FillPortion(1))
 				;
-			let back_button = iced::widget::Row::new()
+			let page_buttons = iced::widget::Row::new()
+				.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+				.push(cancel_button)
+				.push(iced::widget::Space::with_width(iced::Length::FillPortion(12)))
+				.push(apply_button)
 				.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
-				.push(back_button)
-				.push(iced::widget::Space::with_width(iced::Length::FillPortion(14)))
 				;
-			let back_button = iced::widget::Container::new(back_button)
+			let page_buttons = iced::widget::Container::new(page_buttons)
 				.width(iced::Length::Fill)
 				.height(iced::Length::FillPortion(1))
 				;
 			return iced::widget::Column::new()
 				.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
-				.push(back_button)
+				.push(page_buttons)
 				.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
 				.push(page)
 				.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
 				.into()
 		}
-		let remaining_days = (seasons::SEASON_LENGTH as i64) + ((*seasons::CURRENT_SEASON_START) - chrono::Utc::now()).num_days();
-		let remaining_days = format!("{} days remaining in Season {}", remaining_days, (*seasons::CURRENT_SEASON_NUMBER));
+		let tab_bar = iced::widget::Row::new()
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			.push(tab_button("Tracker", Tab::Tracker, self.tab))
+			.push(tab_button("Planner", Tab::Planner, self.tab))
+			.push(tab_button("What If", Tab::Simulator, self.tab))
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			.push(iced::widget::pick_list::PickList::new(&self.profiles.names[..], Some(self.profiles.active.clone()), Message::SwitchProfile))
+			.spacing(8)
+			;
+		let content = match self.tab
+		{
+			Tab::Tracker => self.tracker_view(),
+			Tab::Planner =>
+			{
+				let remaining_xp = self.battle_pass.remaining_xp();
+				self.planner.view(remaining_xp, seasons::get_weekly_resets_left(self.clock.as_ref(), self.battle_pass_deadline), self.play_schedule.remaining_play_days(self.clock.as_ref(), self.battle_pass_deadline, self.region), self.battle_pass_premium, self.required_play_time()).map(Message::PlannerMessage)
+			},
+			Tab::Simulator => self.simulator.view(self.battle_pass, self.clock.as_ref(), self.battle_pass_deadline, self.region).map(Message::SimulatorMessage),
+		};
+
+		let settings_button = iced::widget::Button::new(iced::widget::Text::new("Settings"))
+			.on_press(Message::OpenSettings)
+			.width(iced::Length::FillPortion(1))
+			;
+		let settings_button = iced::widget::Row::new()
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(7)))
+			.push(settings_button)
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			;
+
+		let mut banners = iced::widget::Column::new();
+		if let Some(season_number) = self.new_season_banner
+		{
+			let banner_text = match self.season_archive.last()
+			{
+				Some(archived) => format!("Season {} has started! You finished Season {} at level {}.", season_number, archived.season, archived.level),
+				None => format!("Season {} has started!", season_number),
+			};
+			banners = banners.push(banner(banner_text, vec![("Dismiss", Message::DismissSeasonBanner)]));
+		}
+		if let Some(notice) = &self.settings_notice
+		{
+			let mut buttons = Vec::new();
+			if notice.backup_path.is_some()
+			{
+				buttons.push(("Open backup", Message::OpenSettingsBackup));
+				buttons.push(("Restore backup", Message::RestoreSettingsBackup));
+			}
+			buttons.push(("Dismiss", Message::DismissSettingsNotice));
+			banners = banners.push(banner(notice.message.clone(), buttons));
+		}
+
+		iced::widget::Column::new()
+			.push(banners)
+			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
+			.push(tab_bar)
+			.push(content)
+			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
+			.push(settings_button)
+			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
+			.into()
+	}
+
+	fn theme(&self) -> Self::Theme
+	{
+		self.theme.clone()
+	}
+}
+
+/// A line of text with buttons, shown at the top of the main screen
+fn banner<'a>(text: String, buttons: Vec<(&'a str, Message)>) -> iced::Element<'a, Message>
+{
+	let mut row = iced::widget::Row::new()
+		.push(iced::widget::Text::new(text).size(24).width(iced::Length::Fill))
+		.padding(16)
+		.spacing(8)
+		.align_items(iced::Alignment::Center)
+		;
+	for (label, message) in buttons
+	{
+		row = row.push(iced::widget::Button::new(iced::widget::Text::new(label)).on_press(message));
+	}
+	row.into()
+}
+
+/// Switches to a tab, the open tab can't be pressed
+fn tab_button(label: &str, tab: Tab, selected: Tab) -> iced::Element<'_, Message>
+{
+	let button = iced::widget::Button::new(iced::widget::Text::new(label).horizontal_alignment(iced::alignment::Horizontal::Center))
+		.width(iced::Length::FillPortion(1))
+		;
+	if tab == selected
+	{
+		button.into()
+	}
+	else
+	{
+		button.on_press(Message::SelectTab(tab)).into()
+	}
+}
+
+impl OverHelperApp
+{
+	/// Replaces the state with freshly loaded settings, keeping what isn't stored in the settings file
+	fn apply_loaded_settings(&mut self, settings: &settings::OverHelperSettings)
+	{
+		let history = std::mem::take(&mut self.history);
+		let profiles = std::mem::take(&mut self.profiles);
+		*self = settings::settings_to_appstate(settings, self.clock.clone());
+		self.history = history;
+		self.profiles = profiles;
+		self.check_season_rollover();
+	}
+
+	/// Replaces the state with the settings and history of the active profile
+	fn load_profile(&mut self, profiles: profiles::Profiles)
+	{
+		let loaded_settings = settings::load_settings_or_default(&profiles.active);
+		let loaded_history = history::load_history_or_default(&profiles.active);
+		self.use_profile(profiles, loaded_settings, loaded_history);
+	}
+
+	/// Replaces the state with a profile's settings and history, read by the caller
+	fn use_profile(&mut self, profiles: profiles::Profiles, (settings, settings_notice): (settings::OverHelperSettings, Option<settings::SettingsNotice>), (history, history_notice): (history::LevelHistory, Option<String>))
+	{
+		*self = settings::settings_to_appstate(&settings, self.clock.clone());
+		self.settings_notice = settings_notice;
+		self.history = history;
+		if let Some(history_notice) = history_notice
+		{
+			self.add_settings_notice(history_notice);
+		}
+		self.profiles = profiles;
+		self.check_season_rollover(); // The season may have ended while the app was closed
+		self.record_level(); // Starting point for today's gains, in case the level was never recorded
+	}
+
+	/// Shows a problem below any notice already shown, which keeps its backup buttons
+	fn add_settings_notice(&mut self, message: String)
+	{
+		self.settings_notice = Some(match self.settings_notice.take()
+		{
+			Some(notice) => settings::SettingsNotice { message: format!("{}\n{}", notice.message, message), ..notice },
+			None => settings::SettingsNotice { message, backup_path: None },
+		});
+	}
+
+	/// Writes the settings and history of the active profile
+	fn save(&self) -> Result<(), settings::SettingsError>
+	{
+		settings::write_settings(&self.profiles.active, &settings::appstate_to_settings(self))
+			.and_then(|_| history::write_history(&self.profiles.active, &self.history))
+	}
+
+	/// Adds, renames or deletes a profile from the settings page, the name comes from the page's input
+	fn update_profiles(&mut self, message: settings::Message) -> Result<(), String>
+	{
+		let page = self.settings_page.as_mut().unwrap();
+		match message
+		{
+			settings::Message::CreateProfile =>
+			{
+				let name = profiles::validate_name(&page.profile_name_input, &self.profiles.names)?;
+				profiles::create_profile(&name).map_err(|error| error.to_string())?;
+			},
+			settings::Message::RenameProfile(old_name) =>
+			{
+				let name = profiles::validate_name(&page.profile_name_input, &self.profiles.names)?;
+				profiles::rename_profile(&old_name, &name).map_err(|error| error.to_string())?;
+				if old_name == self.profiles.active
+				{
+					self.profiles.active = name;
+					profiles::write_active_profile(&self.profiles.active).map_err(|error| error.to_string())?;
+				}
+			},
+			// The profile in use can't be deleted, its files are written again on exit
+			settings::Message::ConfirmDeleteProfile(name) if name != self.profiles.active =>
+			{
+				profiles::delete_profile(&name).map_err(|error| error.to_string())?;
+			},
+			_ => (),
+		}
+		page.profile_name_input.clear();
+		page.profile_to_delete = None;
+		self.profiles.refresh().map_err(|error| error.to_string())?;
+		page.profiles = self.profiles.clone();
+		page.backups = settings::list_settings_backups(&self.profiles.active);
+		Ok(())
+	}
+
+	/// When the target will be reached at the rate recorded this season
+	fn projection(&self) -> seasons::Projection
+	{
+		let daily_gains = self.history.tracked_daily_gains(self.season_number, self.region.local_date(self.clock.now()), self.region);
+		seasons::project_completion(&daily_gains, self.battle_pass, self.pace_estimator, self.clock.as_ref(), self.battle_pass_deadline)
+	}
+
+	/// Countdown, level controls and pacing for the tracked battle pass
+	fn tracker_view(&self) -> iced::Element<'_, Message>
+	{
+		let season = seasons::get_current_season(self.clock.as_ref());
+		let countdown = seasons::Countdown::from(seasons::get_remaining_time(self.clock.as_ref(), self.battle_pass_deadline));
+		let format_date = |date: chrono::DateTime<chrono::Utc>| self.region.format(date, "%B %d");
+		// Both dates are shown once there's a deadline, so it's clear which one the numbers use
+		let (remaining_days, goal_dates) = match self.battle_pass_deadline
+		{
+			Some(deadline) if seasons::get_goal_end(self.clock.as_ref(), Some(deadline)) == deadline =>
+			(
+				format!("{} remaining until your deadline", countdown),
+				format!("Your deadline is {}, {} ends {}", format_date(deadline), season, format_date(season.end)),
+			),
+			Some(deadline) if deadline > season.start =>
+			(
+				format!("{} remaining in {}", countdown, season),
+				format!("{} ends {}, before your deadline on {}", season, format_date(season.end), format_date(deadline)),
+			),
+			_ => (format!("{} remaining in {}", countdown, season), String::new()),
+		};
 		let remaining_days = iced::widget::Text::new(remaining_days).size(48);
+		let projection = self.projection();
+		let target = self.battle_pass_target;
+		let projected_finish = match projection.completion
+		{
+			_ if self.battle_pass.is_complete() => String::new(),
+			_ if projection.shortfall(self.battle_pass.target()) > 0 => format!("At your current pace you will miss {} by {} levels", target, projection.shortfall(self.battle_pass.target())),
+			Some(completion) =>
+			{
+				let band = match (projection.earliest_completion, projection.latest_completion)
+				{
+					(Some(earliest), Some(latest)) if earliest != latest => format!(" (between {} and {})", format_date(earliest), format_date(latest)),
+					_ => String::new(),
+				};
+				format!("At your current pace you'll reach {} on {}{}", target, format_date(completion), band)
+			},
+			None => String::from("Play a few days to see when you will finish"),
+		};
+		let progress_chart = self.progress_chart(&projection);
+		let remaining_days = iced::widget::Column::new()
+			.push(remaining_days)
+			.push(iced::widget::Text::new(goal_dates).size(24))
+			.push(iced::widget::Text::new(projected_finish).size(24))
+			.align_items(iced::Alignment::Center)
+			;
 		let remaining_days = iced::widget::Container::new(remaining_days)
-			.width(iced::Length::Fill)
+			.width(iced::Length::FillPortion(1))
 			.center_x()
 			.center_y()
 			;
+		let progress_chart = iced::widget::Canvas::new(progress_chart)
+			.width(iced::Length::FillPortion(1))
+			.height(iced::Length::Units(200))
+			;
+		let remaining_days = iced::widget::Row::new()
+			.push(remaining_days)
+			.push(progress_chart)
+			.padding(16)
+			.align_items(iced::Alignment::Center)
+			;
 
 		let battle_pass_level_display = "Battle pass level";
 		let battle_pass_level_display = iced::widget::Text::new(battle_pass_level_display)
@@ -170,19 +619,27 @@ impl Application for OverHelperApp
 			.width(iced::Length::FillPortion(17)) // 17 is the number of characters in the string
 			;
 		let battle_pass_text_input =
-			iced::widget::TextInput::new(self.battle_pass_level.to_string().as_str(), self.battle_pass_level.to_string().as_str(), Message::UpdateBattlePassTextInput)
+			iced::widget::TextInput::new(self.battle_pass.level().to_string().as_str(), self.battle_pass.level().to_string().as_str(), Message::UpdateBattlePassTextInput)
 			;
 		let battle_pass_text_input = iced::widget::Container::new(battle_pass_text_input)
 			.width(iced::Length::FillPortion(3)) // 3 is the number of characters in the max level
 			.center_x()
 			.center_y()
 			;
+		let battle_pass_xp_text_input =
+			iced::widget::TextInput::new("XP", self.battle_pass.xp().to_string().as_str(), Message::UpdateBattlePassXpTextInput)
+			;
+		let battle_pass_xp_text_input = iced::widget::Container::new(battle_pass_xp_text_input)
+			.width(iced::Length::FillPortion(4)) // 4 is the number of characters in the max XP
+			.center_x()
+			.center_y()
+			;
 		let battle_pass_increment_button = iced::widget::Button::new(iced::widget::Text::new("+"))
-			.on_press(Message::UpdateBattlePassLevel(self.battle_pass_level + 1))
+			.on_press(Message::UpdateBattlePassLevel(self.battle_pass.increment().level()))
 			.width(iced::Length::FillPortion(1))
 			;
 		let battle_pass_decrement_button = iced::widget::Button::new(iced::widget::Text::new("-"))
-			.on_press(Message::UpdateBattlePassLevel(self.battle_pass_level - 1))
+			.on_press(Message::UpdateBattlePassLevel(self.battle_pass.decrement().level()))
 			.width(iced::Length::FillPortion(1))
 			;
 		let battle_pass_buttons = iced::widget::Row::new()
@@ -199,6 +656,9 @@ impl Application for OverHelperApp
 			.push(battle_pass_level_display)
 			.push(battle_pass_text_input)
 			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			.push(battle_pass_xp_text_input)
+			.push(iced::widget::Text::new(format!("/ {} XP", seasons::XP_PER_LEVEL)).width(iced::Length::FillPortion(6)))
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
 			.push(battle_pass_buttons)
 			;
 		let battle_pass_level_display = iced::widget::Container::new(battle_pass_level_display)
@@ -206,7 +666,7 @@ impl Application for OverHelperApp
 			.center_x()
 			.center_y()
 			;
-		let battle_pass_slider = iced::widget::Slider::new(0..=self.battle_pass_target, self.battle_pass_level, Message::UpdateBattlePassLevel)
+		let battle_pass_slider = iced::widget::Slider::new(0..=self.battle_pass.target(), self.battle_pass.level(), Message::UpdateBattlePassLevel)
 			.width(iced::Length::Fill)
 			;
 		let battle_pass_slider = iced::widget::Container::new(battle_pass_slider)
@@ -220,51 +680,178 @@ impl Application for OverHelperApp
 			.push(battle_pass_slider)
 			.padding(16)
 			;
-		let levels_required_per_day = seasons::get_levels_required_per_day(self.battle_pass_level, self.battle_pass_target);
-		let battle_pass_target_information = if levels_required_per_day == 0f64
+		let xp_required_per_day = seasons::get_xp_required_per_day(self.battle_pass, self.clock.as_ref(), self.battle_pass_deadline);
+		let battle_pass_target_information = if xp_required_per_day == 0f64
 		{
-			String::from("You have reached your target! Congratulations!")
+			String::from(seasons::TARGET_REACHED)
 		}
 		else
 		{
-			let target = match self.battle_pass_target
+			let play_time = match self.required_play_time()
+			{
+				Some(play_time) => format!(", about {:.1} hours of play", play_time.hours_per_day),
+				None => String::new(),
+			};
+			let per_session = match seasons::get_xp_required_per_play_day(self.battle_pass, &self.play_schedule, self.clock.as_ref(), self.battle_pass_deadline, self.region)
 			{
-				seasons::MYTHIC_BATTLE_PASS_END => "Mythic Skin".to_string(),
-				seasons::PRESTIGE_BATTLE_PASS_END => "All Prestige Titles".to_string(),
-				level => format!("level {}", level)
+				Some(xp) => format!("{:.2} levels per play session", xp / seasons::XP_PER_LEVEL as f64),
+				None => String::from("no play days left in your schedule"),
 			};
-			format!("{:.3} ({}) levels per day needed to unlock {}", levels_required_per_day, levels_required_per_day.ceil(), target)
+			format!("{:.0} XP ({:.2} levels) per day needed to unlock {}{}\n{}", xp_required_per_day.ceil(), xp_required_per_day / seasons::XP_PER_LEVEL as f64, target, play_time, per_session)
 		};
 		let battle_pass_target_information = iced::widget::Text::new(battle_pass_target_information).size(32);
+		let levels_gained = format!
+		(
+			"Gained {} today, {} this week, {} this season",
+			self.history.levels_gained_today(self.clock.as_ref(), self.region),
+			self.history.levels_gained_this_week(self.clock.as_ref(), self.region),
+			self.history.levels_gained_in_season(self.season_number, self.region),
+		);
+		let battle_pass_target_information = iced::widget::Column::new()
+			.push(battle_pass_target_information)
+			.push(iced::widget::Text::new(levels_gained).size(24))
+			.align_items(iced::Alignment::Center)
+			;
 		let battle_pass_target_information = iced::widget::Container::new(battle_pass_target_information)
 			.width(iced::Length::Fill)
 			.center_x()
 			.center_y()
 			;
 
-		let settings_button = iced::widget::Button::new(iced::widget::Text::new("Settings"))
-			.on_press(Message::OpenSettings)
-			.width(iced::Length::FillPortion(1))
-			;
-		let settings_button = iced::widget::Row::new()
-			.push(iced::widget::Space::with_width(iced::Length::FillPortion(7)))
-			.push(settings_button)
-			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
-			;
-
 		iced::widget::Column::new()
-			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
 			.push(remaining_days)
 			.push(battle_pass_display_and_slider)
 			.push(battle_pass_target_information)
-			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
-			.push(settings_button)
-			.push(iced::widget::Space::with_height(iced::Length::FillPortion(1)))
+			.push(self.role_mastery.view().map(Message::RoleMasteryMessage))
 			.into()
 	}
 
-	fn theme(&self) -> Self::Theme
+	/// Play time needed with the premium pass and boost windows counted
+	fn required_play_time(&self) -> Option<seasons::PlayTime>
 	{
-		self.theme.clone()
+		let remaining_xp = self.battle_pass.remaining_xp();
+		seasons::get_required_play_time
+		(
+			remaining_xp,
+			self.planner.xp_per_hour,
+			self.planner.boost(self.battle_pass_premium),
+			self.planner.max_hours_per_day as f64,
+			&self.boost_windows,
+			&self.play_schedule,
+			self.clock.as_ref(),
+			self.battle_pass_deadline,
+			self.region,
+		)
+	}
+
+	fn progress_chart(&self, projection: &seasons::Projection) -> chart::ProgressChart
+	{
+		let season = seasons::get_current_season(self.clock.as_ref());
+		let now = self.clock.now();
+		let history = self.history.entries.iter()
+			.filter(|entry| entry.season == self.season_number)
+			.map(|entry| (entry.time, entry.level))
+			.collect();
+		chart::ProgressChart
+		{
+			season_start: season.start,
+			season_end: season.end,
+			goal_end: seasons::get_goal_end(self.clock.as_ref(), self.battle_pass_deadline),
+			now,
+			target: self.battle_pass.target(),
+			level: self.battle_pass.fractional_level(),
+			history,
+			levels_required_per_day: seasons::get_xp_required_per_day(self.battle_pass, self.clock.as_ref(), self.battle_pass_deadline) / seasons::XP_PER_LEVEL as f64,
+			projected_finish: projection.completion,
+			region: self.region,
+		}
+	}
+
+	fn record_level(&mut self)
+	{
+		self.history.record(self.clock.now(), self.battle_pass.level(), self.season_number, self.region);
+	}
+
+	/// Archives the tracked season and starts tracking the new one once the season changes
+	fn check_season_rollover(&mut self)
+	{
+		let current_season = seasons::get_current_season(self.clock.as_ref());
+		if current_season.number <= self.season_number
+		{
+			return;
+		}
+		self.season_archive.push(seasons::ArchivedSeason
+		{
+			season: self.season_number,
+			level: self.battle_pass.level(),
+			target: self.battle_pass.target(),
+		});
+		self.season_number = current_season.number;
+		// The new battle pass may be a different length
+		self.battle_pass = seasons::BattlePassProgress::new(0, 0, self.battle_pass_target.level(&current_season));
+		self.record_level(); // Baseline for the new season
+		self.new_season_banner = Some(current_season.number);
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use chrono::TimeZone;
+
+	fn at(month: u32, day: u32, hour: u32) -> std::sync::Arc<dyn clock::Clock>
+	{
+		std::sync::Arc::new(clock::FixedClock(chrono::Utc.with_ymd_and_hms(2023, month, day, hour, 0, 0).unwrap()))
+	}
+
+	// Level 57 in season 2, which ends on February 7 at 18:00
+	fn app_in_season_two() -> OverHelperApp
+	{
+		let mut settings = settings::OverHelperSettings::default();
+		settings.battle_pass.level = 57;
+		settings.battle_pass.season = Some(2);
+		settings::settings_to_appstate(&settings, at(2, 7, 17))
+	}
+
+	#[test]
+	fn projection_only_counts_tracked_days()
+	{
+		// Tracking started two days before the end of season 2, 4 levels in 3 days
+		let mut app = app_in_season_two();
+		app.region = clock::Region::Zone(chrono_tz::UTC);
+		app.pace_estimator = seasons::PaceEstimator::OverallAverage;
+		app.history.record(chrono::Utc.with_ymd_and_hms(2023, 2, 5, 12, 0, 0).unwrap(), 53, 2, app.region);
+		app.history.record(chrono::Utc.with_ymd_and_hms(2023, 2, 6, 12, 0, 0).unwrap(), 57, 2, app.region);
+		assert!((app.projection().levels_per_day - 4.0 / 3.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn nothing_changes_inside_a_season()
+	{
+		let mut app = app_in_season_two();
+		app.check_season_rollover();
+		assert_eq!((app.season_number, app.battle_pass.level()), (2, 57));
+		assert!(app.season_archive.is_empty());
+		assert_eq!(app.new_season_banner, None);
+	}
+
+	#[test]
+	fn new_season_archives_and_resets_the_level()
+	{
+		let mut app = app_in_season_two();
+		app.clock = at(2, 7, 19);
+		app.check_season_rollover();
+		assert_eq!(app.season_archive, vec![seasons::ArchivedSeason { season: 2, level: 57, target: 200 }]);
+		assert_eq!((app.season_number, app.battle_pass.level()), (3, 0));
+		assert_eq!(app.new_season_banner, Some(3));
+		assert_eq!(app.history.entries.last().map(|entry| (entry.season, entry.level)), Some((3, 0)));
+
+		// Later ticks in the same season don't archive again
+		let _ = app.update(Message::Tick);
+		app.clock = at(2, 8, 12);
+		let _ = app.update(Message::Tick);
+		assert_eq!(app.season_archive.len(), 1);
+		assert_eq!(app.season_number, 3);
 	}
 }
@@ -0,0 +1,313 @@
+use crate::seasons;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XpSource
+{
+	DailyChallenges,
+	WeeklyChallenges,
+	MatchWin,
+	MatchLoss,
+	WinRate,
+	PremiumBoost,
+	XpPerHour,
+	MaxHoursPerDay,
+}
+
+pub const XP_SOURCES: [XpSource; 8] = [XpSource::DailyChallenges, XpSource::WeeklyChallenges, XpSource::MatchWin, XpSource::MatchLoss, XpSource::WinRate, XpSource::PremiumBoost, XpSource::XpPerHour, XpSource::MaxHoursPerDay];
+
+impl std::fmt::Display for XpSource
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			XpSource::DailyChallenges => write!(f, "Daily challenges XP"),
+			XpSource::WeeklyChallenges => write!(f, "Weekly challenges XP"),
+			XpSource::MatchWin => write!(f, "XP per win"),
+			XpSource::MatchLoss => write!(f, "XP per loss"),
+			XpSource::WinRate => write!(f, "Win rate %"),
+			XpSource::PremiumBoost => write!(f, "Premium XP boost %"),
+			XpSource::XpPerHour => write!(f, "XP per hour played"),
+			XpSource::MaxHoursPerDay => write!(f, "Hours you can play a day"),
+		}
+	}
+}
+
+/// How much XP the player expects from each source, challenge XP is the total for all challenges of a day or week
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Planner
+{
+	pub daily_challenge_xp: u32,
+	pub weekly_challenge_xp: u32,
+	pub win_xp: u32,
+	pub loss_xp: u32,
+	pub win_rate: u32, // Percent of matches won
+	pub premium_boost: u32, // Percent added to match XP when the premium pass is owned
+	pub xp_per_hour: u32, // Without boosts
+	pub max_hours_per_day: u32,
+}
+
+/// XP still needed after challenges and how many matches it takes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plan
+{
+	pub challenge_xp: u32,
+	pub match_xp: u32,
+	pub xp_per_match: f64,
+	pub matches: u32,
+	pub matches_per_session: f64,
+}
+
+impl Planner
+{
+	pub fn value(&self, source: XpSource) -> u32
+	{
+		match source
+		{
+			XpSource::DailyChallenges => self.daily_challenge_xp,
+			XpSource::WeeklyChallenges => self.weekly_challenge_xp,
+			XpSource::MatchWin => self.win_xp,
+			XpSource::MatchLoss => self.loss_xp,
+			XpSource::WinRate => self.win_rate,
+			XpSource::PremiumBoost => self.premium_boost,
+			XpSource::XpPerHour => self.xp_per_hour,
+			XpSource::MaxHoursPerDay => self.max_hours_per_day,
+		}
+	}
+
+	fn value_mut(&mut self, source: XpSource) -> &mut u32
+	{
+		match source
+		{
+			XpSource::DailyChallenges => &mut self.daily_challenge_xp,
+			XpSource::WeeklyChallenges => &mut self.weekly_challenge_xp,
+			XpSource::MatchWin => &mut self.win_xp,
+			XpSource::MatchLoss => &mut self.loss_xp,
+			XpSource::WinRate => &mut self.win_rate,
+			XpSource::PremiumBoost => &mut self.premium_boost,
+			XpSource::XpPerHour => &mut self.xp_per_hour,
+			XpSource::MaxHoursPerDay => &mut self.max_hours_per_day,
+		}
+	}
+
+	/// Fraction added to match XP, 0 without the premium pass
+	pub fn boost(&self, premium: bool) -> f64
+	{
+		if premium
+		{
+			self.premium_boost as f64 / 100.0
+		}
+		else
+		{
+			0.0
+		}
+	}
+
+	/// Average XP of one match, boost included
+	pub fn xp_per_match(&self, premium: bool) -> f64
+	{
+		let win_rate = self.win_rate as f64 / 100.0;
+		let match_xp = self.win_xp as f64 * win_rate + self.loss_xp as f64 * (1.0 - win_rate);
+		match_xp * (1.0 + self.boost(premium))
+	}
+
+	/// Splits the remaining XP between challenges and matches, assuming every challenge gets done.
+	/// Daily challenges and matches only happen on play days, weekly challenges count once per weekly reset left
+	pub fn plan(&self, remaining_xp: u32, weekly_resets: u32, play_days: f64, premium: bool) -> Plan
+	{
+		let weekly_challenges = if play_days > 0.0 { weekly_resets as f64 } else { 0.0 };
+		let challenge_xp = self.daily_challenge_xp as f64 * play_days.ceil() + self.weekly_challenge_xp as f64 * weekly_challenges;
+		let challenge_xp = std::cmp::min(challenge_xp as u32, remaining_xp);
+		let match_xp = remaining_xp - challenge_xp;
+		let xp_per_match = self.xp_per_match(premium);
+		let matches = if match_xp == 0
+		{
+			0
+		}
+		else if xp_per_match > 0.0
+		{
+			(match_xp as f64 / xp_per_match).ceil() as u32
+		}
+		else
+		{
+			u32::MAX // Matches give nothing, the target can't be reached by playing
+		};
+		Plan
+		{
+			challenge_xp,
+			match_xp,
+			xp_per_match,
+			matches,
+			matches_per_session: matches as f64 / play_days.max(1.0),
+		}
+	}
+
+	pub fn update(&mut self, message: Message)
+	{
+		match message
+		{
+			Message::ValueChanged(source, value) =>
+			{
+				if value.is_empty()
+				{
+					*self.value_mut(source) = 0;
+				}
+				else if let Ok(value) = value.parse::<u32>()
+				{
+					*self.value_mut(source) = match source
+					{
+						XpSource::WinRate => std::cmp::min(value, 100),
+						XpSource::MaxHoursPerDay => std::cmp::min(value, 24),
+						_ => value,
+					};
+				}
+			},
+		}
+	}
+
+	pub fn view(&self, remaining_xp: u32, weekly_resets: u32, play_days: f64, premium: bool, play_time: Option<seasons::PlayTime>) -> iced::Element<'_, Message>
+	{
+		let mut panel = iced::widget::Column::new()
+			.push
+			(
+				iced::widget::Text::new("XP Planner")
+					.size(32)
+					.horizontal_alignment(iced::alignment::Horizontal::Center)
+					.width(iced::Length::Fill)
+			)
+			.spacing(8)
+			.padding(16)
+			;
+		for source in XP_SOURCES
+		{
+			let value_input = iced::widget::TextInput::new("0", &self.value(source).to_string(), move |value| Message::ValueChanged(source, value))
+				.width(iced::Length::FillPortion(1))
+				;
+			panel = panel.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::Text::new(source.to_string()).size(24).width(iced::Length::FillPortion(2)))
+					.push(value_input)
+					.push(iced::widget::Space::with_width(iced::Length::FillPortion(3)))
+					.spacing(8)
+					.align_items(iced::Alignment::Center)
+			);
+		}
+
+		let plan = self.plan(remaining_xp, weekly_resets, play_days, premium);
+		// The challenge line is only shown when the summary doesn't already cover challenges
+		let (challenges, summary) = if remaining_xp == 0
+		{
+			(String::new(), String::from(seasons::TARGET_REACHED))
+		}
+		else if plan.match_xp == 0
+		{
+			(String::new(), format!("Challenges alone cover the remaining {} XP", remaining_xp))
+		}
+		else if plan.matches == u32::MAX
+		{
+			(String::new(), format!("Challenges cover {} of the remaining {} XP, set the XP per match to plan the rest", plan.challenge_xp, remaining_xp))
+		}
+		else
+		{
+			(
+				format!("Challenges cover {} of the remaining {} XP", plan.challenge_xp, remaining_xp),
+				format!("{:.1} matches per play session needed, {} in total at {:.0} XP each", plan.matches_per_session, plan.matches, plan.xp_per_match),
+			)
+		};
+		let play_time = match play_time
+		{
+			_ if remaining_xp == 0 => String::new(),
+			Some(play_time) =>
+			{
+				let boost_windows = if play_time.boost_days == 0.0
+				{
+					String::from("No XP boosts are left this season")
+				}
+				else if play_time.boost_windows_are_enough(remaining_xp)
+				{
+					format!("Playing {} hours a day during XP boosts is enough on its own", self.max_hours_per_day)
+				}
+				else
+				{
+					format!("Playing {} hours a day during XP boosts only earns {:.0} of the {} XP, don't wait for them", self.max_hours_per_day, play_time.boost_windows_xp, remaining_xp)
+				};
+				format!("About {:.1} hours of play a day ({:.0} hours in total) with your boosts\n{}", play_time.hours_per_day, play_time.total_hours, boost_windows)
+			},
+			None => String::from("Set the XP per hour to see how long you need to play"),
+		};
+		if !challenges.is_empty()
+		{
+			panel = panel.push(iced::widget::Text::new(challenges).size(24));
+		}
+		panel
+			.push(iced::widget::Text::new(summary).size(32))
+			.push(iced::widget::Text::new(play_time).size(24))
+			.into()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum Message
+{
+	ValueChanged(XpSource, String),
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn planner() -> Planner
+	{
+		Planner { daily_challenge_xp: 1_000, weekly_challenge_xp: 7_000, win_xp: 1_000, loss_xp: 500, win_rate: 50, premium_boost: 20, xp_per_hour: 5_000, max_hours_per_day: 4 }
+	}
+
+	#[test]
+	fn matches_cover_what_challenges_leave()
+	{
+		// 14 days with 2 weekly resets give 14 * 1000 + 2 * 7000 = 28000 XP
+		let plan = planner().plan(100_000, 2, 14.0, false);
+		assert_eq!(plan.challenge_xp, 28_000);
+		assert_eq!(plan.match_xp, 72_000);
+		assert_eq!(plan.xp_per_match, 750.0);
+		assert_eq!(plan.matches, 96);
+		assert!((plan.matches_per_session - 96.0 / 14.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn boost_and_challenges_reduce_matches()
+	{
+		assert_eq!(planner().xp_per_match(true), 900.0);
+		assert_eq!(planner().plan(100_000, 2, 14.0, true).matches, 80);
+		assert_eq!(planner().plan(10_000, 2, 14.0, false).matches, 0);
+		assert_eq!(planner().plan(0, 0, 0.0, false).matches_per_session, 0.0);
+	}
+
+	#[test]
+	fn daily_challenges_only_on_play_days()
+	{
+		// Weekends only, 4 play days in 14 days give 4 * 1000 + 2 * 7000 = 18000 XP
+		let plan = planner().plan(100_000, 2, 4.0, false);
+		assert_eq!(plan.challenge_xp, 18_000);
+		assert_eq!(plan.matches, 110);
+		assert!((plan.matches_per_session - 110.0 / 4.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn weekly_challenges_count_resets_left()
+	{
+		use chrono::TimeZone;
+
+		// From Saturday, the next reset is Tuesday at 18:00
+		let now = chrono::Utc.with_ymd_and_hms(2023, 1, 14, 12, 0, 0).unwrap();
+		let reset = chrono::Utc.with_ymd_and_hms(2023, 1, 17, 18, 0, 0).unwrap();
+		let before_reset = seasons::weekly_resets_between(now, reset - chrono::Duration::minutes(1), 18);
+		let after_reset = seasons::weekly_resets_between(now, reset + chrono::Duration::minutes(1), 18);
+		assert_eq!((before_reset, after_reset), (0, 1));
+		assert_eq!(planner().plan(100_000, before_reset, 4.0, false).challenge_xp, 4_000);
+		assert_eq!(planner().plan(100_000, after_reset, 4.0, false).challenge_xp, 11_000);
+
+		assert_eq!(seasons::weekly_resets_between(reset, reset + chrono::Duration::weeks(2), 18), 1); // Neither end counts
+	}
+}
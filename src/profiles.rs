@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::SettingsError;
+
+/// Profile the files written before profiles existed are moved into
+pub const DEFAULT_PROFILE: &str = "Default";
+const MAX_NAME_LENGTH: usize = 32;
+
+/*
+Layout of the config directory:
+	seasons.json            Shared by every profile
+	profiles.json           { "active": "Main" }
+	profiles/Main/settings.json
+	profiles/Main/history.json
+*/
+// Only the active profile is stored, the profiles themselves are the directories
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfilesFile
+{
+	active: String,
+}
+
+impl Default for ProfilesFile
+{
+	fn default() -> Self
+	{
+		Self { active: String::from(DEFAULT_PROFILE) }
+	}
+}
+
+/// The profile in use and the names of all profiles, sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profiles
+{
+	pub active: String,
+	pub names: Vec<String>,
+}
+
+impl Default for Profiles
+{
+	fn default() -> Self
+	{
+		Self { active: String::from(DEFAULT_PROFILE), names: vec![String::from(DEFAULT_PROFILE)] }
+	}
+}
+
+impl Profiles
+{
+	/// Reads the profiles, moving files from before profiles existed into the default one first.
+	/// Debug builds leave the files where they are, like every other write
+	pub fn load() -> Result<Self, SettingsError>
+	{
+		let config_dir = crate::settings::get_config_dir()?;
+		if crate::settings::SAVES_FILES
+		{
+			move_into_default_profile(&config_dir)?;
+		}
+		Self::read_from(&config_dir)
+	}
+
+	/// Reads the profiles without moving or creating anything, for commands that only look
+	pub fn read() -> Result<Self, SettingsError>
+	{
+		Self::read_from(&crate::settings::get_config_dir()?)
+	}
+
+	fn read_from(config_dir: &std::path::Path) -> Result<Self, SettingsError>
+	{
+		let names = list_profiles(&config_dir.join("profiles"))?;
+		let profiles_path = config_dir.join("profiles.json");
+		let active = match std::fs::read(&profiles_path)
+		{
+			Ok(contents) => serde_json::from_slice::<ProfilesFile>(&contents).map_err(|source| SettingsError::Parse { path: profiles_path, source })?.active,
+			Err(_) => String::from(DEFAULT_PROFILE),
+		};
+		// The remembered profile may have been removed by hand
+		let active = if names.contains(&active) { active } else { names[0].clone() };
+		Ok(Self { active, names })
+	}
+
+	/// Re-reads the profile names after one was added, renamed or removed
+	pub fn refresh(&mut self) -> Result<(), SettingsError>
+	{
+		self.names = list_profiles(&get_profiles_dir()?)?;
+		Ok(())
+	}
+}
+
+/// Remembers which profile to open next time
+pub fn write_active_profile(name: &str) -> Result<(), SettingsError>
+{
+	if !crate::settings::SAVES_FILES
+	{
+		return Ok(());
+	}
+	let profiles_path = crate::settings::get_config_dir()?.join("profiles.json");
+	let contents = serde_json::to_vec_pretty(&ProfilesFile { active: String::from(name) }).map_err(|source| SettingsError::Serialize { path: profiles_path.clone(), source })?;
+	crate::settings::write_file_atomically(&profiles_path, &contents)
+}
+
+pub fn get_profiles_dir() -> Result<std::path::PathBuf, SettingsError>
+{
+	Ok(crate::settings::get_config_dir()?.join("profiles"))
+}
+
+/// Directory holding a profile's settings and history, created if needed in builds that save
+pub fn get_profile_dir(name: &str) -> Result<std::path::PathBuf, SettingsError>
+{
+	let profile_dir = get_profiles_dir()?.join(name);
+	if crate::settings::SAVES_FILES
+	{
+		std::fs::create_dir_all(&profile_dir).map_err(SettingsError::io("create", &profile_dir))?;
+	}
+	Ok(profile_dir)
+}
+
+/// Checks a name typed by the user, returning it trimmed.
+/// Names become directory names, so they are kept to characters every OS accepts
+pub fn validate_name(name: &str, existing: &[String]) -> Result<String, String>
+{
+	let name = name.trim();
+	if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH
+	{
+		return Err(format!("Profile names need 1 to {} characters", MAX_NAME_LENGTH));
+	}
+	if !name.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+	{
+		return Err(String::from("Profile names can only use letters, numbers, spaces, - and _"));
+	}
+	// Some filesystems ignore case
+	if existing.iter().any(|existing| existing.to_lowercase() == name.to_lowercase())
+	{
+		return Err(format!("There is already a profile called {}", name));
+	}
+	Ok(String::from(name))
+}
+
+pub fn create_profile(name: &str) -> Result<(), SettingsError>
+{
+	let profiles_dir = get_profiles_dir()?;
+	std::fs::create_dir_all(&profiles_dir).map_err(SettingsError::io("create", &profiles_dir))?; // Missing until something is saved in debug builds
+	let profile_dir = profiles_dir.join(name);
+	std::fs::create_dir(&profile_dir).map_err(SettingsError::io("create", &profile_dir))
+}
+
+pub fn rename_profile(old_name: &str, new_name: &str) -> Result<(), SettingsError>
+{
+	let profiles_dir = get_profiles_dir()?;
+	let old_dir = profiles_dir.join(old_name);
+	std::fs::rename(&old_dir, profiles_dir.join(new_name)).map_err(SettingsError::io("rename", &old_dir))
+}
+
+/// Removes a profile with its settings, history and backups
+pub fn delete_profile(name: &str) -> Result<(), SettingsError>
+{
+	let profile_dir = get_profiles_dir()?.join(name);
+	std::fs::remove_dir_all(&profile_dir).map_err(SettingsError::io("delete", &profile_dir))
+}
+
+/// Profile directory names, sorted, with just the default profile when there are none.
+/// Its directory is created once something is saved in it
+fn list_profiles(profiles_dir: &std::path::Path) -> Result<Vec<String>, SettingsError>
+{
+	let mut names: Vec<String> = match std::fs::read_dir(profiles_dir)
+	{
+		Ok(entries) => entries
+			.filter_map(|entry| entry.ok())
+			.filter(|entry| entry.path().is_dir())
+			.filter_map(|entry| entry.file_name().into_string().ok())
+			.collect(),
+		Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+		Err(error) => return Err(SettingsError::io("read", profiles_dir)(error)),
+	};
+	if names.is_empty()
+	{
+		names.push(String::from(DEFAULT_PROFILE));
+	}
+	names.sort_by_key(|name| name.to_lowercase());
+	Ok(names)
+}
+
+/// Moves settings.json, its backups and history.json from the config directory into the default profile.
+/// Only runs once, before the profiles directory exists
+fn move_into_default_profile(config_dir: &std::path::Path) -> Result<(), SettingsError>
+{
+	let profiles_dir = config_dir.join("profiles");
+	if profiles_dir.exists()
+	{
+		return Ok(());
+	}
+	let default_dir = profiles_dir.join(DEFAULT_PROFILE);
+	std::fs::create_dir_all(&default_dir).map_err(SettingsError::io("create", &default_dir))?;
+	for entry in std::fs::read_dir(config_dir).map_err(SettingsError::io("read", config_dir))?.filter_map(|entry| entry.ok())
+	{
+		let file_name = entry.file_name().to_string_lossy().to_string();
+		if file_name.starts_with("settings.json") || file_name == "history.json"
+		{
+			std::fs::rename(entry.path(), default_dir.join(&file_name)).map_err(SettingsError::io("move", &entry.path()))?;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn old_files_move_into_default_profile()
+	{
+		let dir = crate::settings::test_dir("profiles");
+		for file_name in ["settings.json", "settings.json.1", "history.json", "seasons.json"]
+		{
+			std::fs::write(dir.join(file_name), file_name).unwrap();
+		}
+		move_into_default_profile(&dir).unwrap();
+
+		let default_dir = dir.join("profiles").join(DEFAULT_PROFILE);
+		for file_name in ["settings.json", "settings.json.1", "history.json"]
+		{
+			assert_eq!(std::fs::read_to_string(default_dir.join(file_name)).unwrap(), file_name);
+			assert!(!dir.join(file_name).exists());
+		}
+		assert!(dir.join("seasons.json").exists()); // Shared by all profiles
+
+		// Runs only once, later files at the top are left alone
+		std::fs::write(dir.join("settings.json"), "new").unwrap();
+		move_into_default_profile(&dir).unwrap();
+		assert_eq!(std::fs::read_to_string(default_dir.join("settings.json")).unwrap(), "settings.json");
+		assert_eq!(list_profiles(&dir.join("profiles")).unwrap(), vec![String::from(DEFAULT_PROFILE)]);
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn reading_profiles_creates_nothing()
+	{
+		let dir = crate::settings::test_dir("profiles-read");
+		std::fs::write(dir.join("settings.json"), "{}").unwrap();
+		assert_eq!(Profiles::read_from(&dir).unwrap(), Profiles::default());
+		assert!(!dir.join("profiles").exists());
+		assert!(dir.join("settings.json").exists());
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn profile_names_are_checked()
+	{
+		let existing = vec![String::from("Main")];
+		assert_eq!(validate_name("  Alt account ", &existing), Ok(String::from("Alt account")));
+		assert!(validate_name("main", &existing).is_err());
+		assert!(validate_name("", &existing).is_err());
+		assert!(validate_name("../settings", &existing).is_err());
+		assert!(validate_name(&"a".repeat(MAX_NAME_LENGTH + 1), &existing).is_err());
+	}
+}
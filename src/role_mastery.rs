@@ -0,0 +1,241 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role
+{
+	Tank,
+	Damage,
+	Support,
+}
+
+pub const ROLES: [Role; 3] = [Role::Tank, Role::Damage, Role::Support];
+
+impl std::fmt::Display for Role
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Role::Tank => write!(f, "Tank"),
+			Role::Damage => write!(f, "Damage"),
+			Role::Support => write!(f, "Support"),
+		}
+	}
+}
+
+/// Wins per role and the number of wins the player is aiming for, a goal of 0 means no goal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoleMastery
+{
+	pub tank_wins: u8,
+	pub damage_wins: u8,
+	pub support_wins: u8,
+
+	pub tank_goal: u8,
+	pub damage_goal: u8,
+	pub support_goal: u8,
+}
+
+impl RoleMastery
+{
+	pub fn wins(&self, role: Role) -> u8
+	{
+		match role
+		{
+			Role::Tank => self.tank_wins,
+			Role::Damage => self.damage_wins,
+			Role::Support => self.support_wins,
+		}
+	}
+
+	pub fn goal(&self, role: Role) -> u8
+	{
+		match role
+		{
+			Role::Tank => self.tank_goal,
+			Role::Damage => self.damage_goal,
+			Role::Support => self.support_goal,
+		}
+	}
+
+	fn wins_mut(&mut self, role: Role) -> &mut u8
+	{
+		match role
+		{
+			Role::Tank => &mut self.tank_wins,
+			Role::Damage => &mut self.damage_wins,
+			Role::Support => &mut self.support_wins,
+		}
+	}
+
+	fn goal_mut(&mut self, role: Role) -> &mut u8
+	{
+		match role
+		{
+			Role::Tank => &mut self.tank_goal,
+			Role::Damage => &mut self.damage_goal,
+			Role::Support => &mut self.support_goal,
+		}
+	}
+
+	pub fn update(&mut self, message: Message)
+	{
+		match message
+		{
+			Message::IncrementWins(role) =>
+			{
+				let wins = self.wins_mut(role);
+				*wins = wins.saturating_add(1);
+			},
+			Message::DecrementWins(role) =>
+			{
+				let wins = self.wins_mut(role);
+				*wins = wins.saturating_sub(1);
+			},
+			Message::WinsChanged(role, wins) =>
+			{
+				if wins.is_empty()
+				{
+					*self.wins_mut(role) = 0;
+				}
+				else if let Ok(wins) = wins.parse()
+				{
+					*self.wins_mut(role) = wins;
+				}
+			},
+			Message::GoalChanged(role, goal) =>
+			{
+				if goal.is_empty()
+				{
+					*self.goal_mut(role) = 0;
+				}
+				else if let Ok(goal) = goal.parse()
+				{
+					*self.goal_mut(role) = goal;
+				}
+			},
+		}
+	}
+
+	pub fn view(&self) -> iced::Element<'_, Message>
+	{
+		let mut panel = iced::widget::Column::new()
+			.push
+			(
+				iced::widget::Text::new("Role Mastery")
+					.size(32)
+					.horizontal_alignment(iced::alignment::Horizontal::Center)
+					.width(iced::Length::Fill)
+			)
+			.spacing(8)
+			.padding(16)
+			;
+		for role in ROLES
+		{
+			panel = panel.push(self.role_view(role));
+		}
+		panel.into()
+	}
+
+	fn role_view(&self, role: Role) -> iced::Element<'_, Message>
+	{
+		let wins = self.wins(role);
+		let goal = self.goal(role);
+
+		let role_name = iced::widget::Text::new(role.to_string())
+			.size(24)
+			.width(iced::Length::FillPortion(2))
+			;
+		let wins_input = iced::widget::TextInput::new("Wins", &wins.to_string(), move |wins| Message::WinsChanged(role, wins))
+			.width(iced::Length::FillPortion(1))
+			;
+		let decrement_button = iced::widget::Button::new(iced::widget::Text::new("-"))
+			.on_press(Message::DecrementWins(role))
+			;
+		let increment_button = iced::widget::Button::new(iced::widget::Text::new("+"))
+			.on_press(Message::IncrementWins(role))
+			;
+		let goal_input = iced::widget::TextInput::new("Goal", &if goal == 0 { String::new() } else { goal.to_string() }, move |goal| Message::GoalChanged(role, goal))
+			.width(iced::Length::FillPortion(1))
+			;
+		let progress: iced::Element<_> = if goal == 0
+		{
+			iced::widget::Text::new("No goal set").width(iced::Length::FillPortion(6)).into()
+		}
+		else
+		{
+			let progress_bar = iced::widget::ProgressBar::new(0.0..=goal as f32, wins as f32)
+				.height(iced::Length::Units(16))
+				;
+			iced::widget::Row::new()
+				.push(iced::widget::Container::new(progress_bar).width(iced::Length::FillPortion(5)).center_y())
+				.push(iced::widget::Text::new(format!("{}/{}", std::cmp::min(wins, goal), goal)).width(iced::Length::FillPortion(1)))
+				.spacing(8)
+				.width(iced::Length::FillPortion(6))
+				.into()
+		};
+
+		iced::widget::Row::new()
+			.push(role_name)
+			.push(wins_input)
+			.push(decrement_button)
+			.push(increment_button)
+			.push(iced::widget::Text::new("Goal"))
+			.push(goal_input)
+			.push(progress)
+			.spacing(8)
+			.align_items(iced::Alignment::Center)
+			.into()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum Message
+{
+	IncrementWins(Role),
+	DecrementWins(Role),
+	WinsChanged(Role, String),
+	GoalChanged(Role, String),
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn typed_values_are_parsed()
+	{
+		let mut mastery = RoleMastery::default();
+		mastery.update(Message::WinsChanged(Role::Damage, String::from("12")));
+		mastery.update(Message::GoalChanged(Role::Damage, String::from("50")));
+		assert_eq!((mastery.wins(Role::Damage), mastery.goal(Role::Damage)), (12, 50));
+
+		// Anything that isn't a number keeps the last value
+		mastery.update(Message::WinsChanged(Role::Damage, String::from("12a")));
+		mastery.update(Message::GoalChanged(Role::Damage, String::from("-5")));
+		assert_eq!((mastery.wins(Role::Damage), mastery.goal(Role::Damage)), (12, 50));
+		assert_eq!(mastery.wins(Role::Tank), 0);
+	}
+
+	#[test]
+	fn empty_inputs_clear_to_zero()
+	{
+		let mut mastery = RoleMastery { support_wins: 7, support_goal: 20, ..RoleMastery::default() };
+		mastery.update(Message::WinsChanged(Role::Support, String::new()));
+		mastery.update(Message::GoalChanged(Role::Support, String::new()));
+		assert_eq!((mastery.wins(Role::Support), mastery.goal(Role::Support)), (0, 0));
+	}
+
+	#[test]
+	fn wins_never_overflow()
+	{
+		let mut mastery = RoleMastery { tank_wins: u8::MAX, ..RoleMastery::default() };
+		mastery.update(Message::IncrementWins(Role::Tank));
+		assert_eq!(mastery.wins(Role::Tank), u8::MAX);
+		mastery.update(Message::WinsChanged(Role::Tank, String::from("256")));
+		mastery.update(Message::GoalChanged(Role::Tank, String::from("1000")));
+		assert_eq!((mastery.wins(Role::Tank), mastery.goal(Role::Tank)), (u8::MAX, 0));
+
+		mastery.update(Message::DecrementWins(Role::Damage));
+		assert_eq!(mastery.wins(Role::Damage), 0);
+	}
+}
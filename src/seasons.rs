@@ -1,8 +1,15 @@
 use chrono::TimeZone;
 
+use crate::clock::{Clock, Region};
+
+/// A battle pass level or tier
+pub type Level = u16;
+
 pub const SEASON_LENGTH: u8 = 63;
-pub const PRESTIGE_BATTLE_PASS_END: u8 = 200;
-pub const MYTHIC_BATTLE_PASS_END: u8 = 80;
+pub const XP_PER_LEVEL: u32 = 10_000;
+
+// Season dates shipped with the app, can be extended by a seasons.json in the config directory
+const BUNDLED_SEASONS: &str = include_str!("../data/seasons.json");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BattlePassTargets
@@ -26,51 +33,1103 @@ impl std::fmt::Display for BattlePassTargets
 	}
 }
 
+/// The level a player is aiming for, stored in the settings file as {"kind": "custom", "level": 120}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", from = "RawBattlePassTarget")]
+pub enum BattlePassTarget
+{
+	Mythic,
+	#[default]
+	Prestige,
+	Custom { level: Level },
+}
+
+// Unvalidated form of BattlePassTarget, as read from a file
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum RawBattlePassTarget
+{
+	Mythic,
+	Prestige,
+	Custom { level: Level },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBattlePassTarget(pub Level);
+
+impl std::fmt::Display for InvalidBattlePassTarget
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "Target level {} is outside the battle pass (1 to {})", self.0, SEASON_CALENDAR.max_battle_pass_levels())
+	}
+}
+
+// A level outside the battle pass only resets the target, the rest of the settings file still loads.
+// The bounds come from the season calendar, so editing seasons.json can make a saved level invalid
+impl From<RawBattlePassTarget> for BattlePassTarget
+{
+	fn from(target: RawBattlePassTarget) -> Self
+	{
+		match target
+		{
+			RawBattlePassTarget::Mythic => BattlePassTarget::Mythic,
+			RawBattlePassTarget::Prestige => BattlePassTarget::Prestige,
+			RawBattlePassTarget::Custom { level } => BattlePassTarget::custom(level).unwrap_or_default(),
+		}
+	}
+}
+
+impl std::fmt::Display for BattlePassTarget
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			BattlePassTarget::Mythic => write!(f, "Mythic Skin"),
+			BattlePassTarget::Prestige => write!(f, "All Prestige Titles"),
+			BattlePassTarget::Custom { level } => write!(f, "level {}", level),
+		}
+	}
+}
+
+impl BattlePassTarget
+{
+	/// A custom target, which has to be a level of the longest battle pass in the calendar.
+	/// In seasons with a shorter battle pass it counts as the last level
+	pub fn custom(level: Level) -> Result<Self, InvalidBattlePassTarget>
+	{
+		if (1..=SEASON_CALENDAR.max_battle_pass_levels()).contains(&level)
+		{
+			Ok(BattlePassTarget::Custom { level })
+		}
+		else
+		{
+			Err(InvalidBattlePassTarget(level))
+		}
+	}
+
+	/// Level the target stands for in a season's battle pass
+	pub fn level(self, season: &Season) -> Level
+	{
+		match self
+		{
+			BattlePassTarget::Mythic => season.mythic_level,
+			BattlePassTarget::Prestige => season.battle_pass_levels,
+			BattlePassTarget::Custom { level } => std::cmp::min(level, season.battle_pass_levels),
+		}
+	}
+
+	pub fn kind(self) -> BattlePassTargets
+	{
+		match self
+		{
+			BattlePassTarget::Mythic => BattlePassTargets::Mythic,
+			BattlePassTarget::Prestige => BattlePassTargets::Prestige,
+			BattlePassTarget::Custom { .. } => BattlePassTargets::Custom,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Season
+{
+	pub number: u8,
+	pub start: chrono::DateTime<chrono::Utc>,
+	pub end: chrono::DateTime<chrono::Utc>,
+	pub name: Option<String>,
+	pub battle_pass_levels: Level, // Prestige titles included
+	pub mythic_level: Level,
+}
+
+impl std::fmt::Display for Season
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match &self.name
+		{
+			Some(name) => write!(f, "Season {}: {}", self.number, name),
+			None => write!(f, "Season {}", self.number)
+		}
+	}
+}
+
+/// Final battle pass level of a finished season
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ArchivedSeason
+{
+	pub season: u8,
+	pub level: Level,
+	pub target: Level,
+}
+
+// What a season has unless it says otherwise
+#[derive(Debug, Clone, Copy)]
+struct SeasonDefaults
+{
+	reset_hour: u32, // UTC hour seasons and events start at
+	battle_pass_levels: Level,
+	mythic_level: Level,
+}
+
+impl Default for SeasonDefaults
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			reset_hour: 0,
+			battle_pass_levels: 200,
+			mythic_level: 80,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct SeasonCalendar
+{
+	seasons: Vec<Season>, // Sorted by start date
+	defaults: SeasonDefaults, // Also used for seasons guessed past the end of the calendar
+	override_error: Option<String>, // Why the user's seasons.json was ignored
+}
+
+impl SeasonCalendar
+{
+	/// Calendar containing only the seasons bundled with the app
+	pub fn bundled() -> Self
+	{
+		Self::from_json(BUNDLED_SEASONS, SeasonDefaults::default()).expect("Bundled season data is invalid")
+	}
+
+	/// Bundled calendar with the user's seasons.json applied on top
+	pub fn load() -> Self
+	{
+		let mut calendar = Self::bundled();
+		let override_path = match crate::settings::get_config_dir()
+		{
+			Ok(config_dir) => config_dir.join("seasons.json"),
+			Err(_) => return calendar,
+		};
+		if let Ok(contents) = std::fs::read_to_string(&override_path)
+		{
+			match Self::from_json(&contents, calendar.defaults)
+			{
+				Some(overrides) => calendar.merge(overrides),
+				None => calendar.override_error = Some(format!("Could not parse {}, using the bundled season dates", override_path.display())),
+			}
+		}
+		calendar
+	}
+
+	/// Problem with the user's seasons.json, to show the user
+	pub fn override_error(&self) -> Option<&str>
+	{
+		self.override_error.as_deref()
+	}
+
+	/*
+	Example file, dates without a time start at the reset hour (UTC), battle pass sizes can be set per season:
+	{
+		"reset_hour": 18,
+		"battle_pass_levels": 200,
+		"mythic_level": 80,
+		"seasons":
+		[
+			{ "number": 6, "start": "2023-08-10", "end": "2023-10-10", "name": "Invasion", "battle_pass_levels": 250 }
+		]
+	}
+	*/
+	fn from_json(json: &str, defaults: SeasonDefaults) -> Option<Self>
+	{
+		let json: serde_json::Value = serde_json::from_str(json).ok()?;
+		// Missing values are inherited, present ones have to be valid
+		let reset_hour = match json.get("reset_hour")
+		{
+			Some(reset_hour) => u32::try_from(reset_hour.as_u64()?).ok().filter(|hour| *hour < 24)?,
+			None => defaults.reset_hour,
+		};
+		let level = |value: &serde_json::Value, key: &str, default: Level| match value.get(key)
+		{
+			Some(level) => Level::try_from(level.as_u64()?).ok().filter(|level| *level >= 1),
+			None => Some(default),
+		};
+		let battle_pass_levels = level(&json, "battle_pass_levels", defaults.battle_pass_levels)?;
+		let mythic_level = level(&json, "mythic_level", defaults.mythic_level)?;
+		let mut seasons = Vec::new();
+		for season in json["seasons"].as_array()?
+		{
+			let number = u8::try_from(season["number"].as_u64()?).ok()?;
+			let start = parse_season_date(season["start"].as_str()?, reset_hour)?;
+			let end = parse_season_date(season["end"].as_str()?, reset_hour)?;
+			if end <= start
+			{
+				return None;
+			}
+			let name = season["name"].as_str().map(String::from);
+			let battle_pass_levels = level(season, "battle_pass_levels", battle_pass_levels)?;
+			let mythic_level = std::cmp::min(level(season, "mythic_level", mythic_level)?, battle_pass_levels);
+			seasons.push(Season { number, start, end, name, battle_pass_levels, mythic_level });
+		}
+		seasons.sort_by_key(|season| season.start);
+		Some(Self { seasons, defaults: SeasonDefaults { reset_hour, battle_pass_levels, mythic_level }, override_error: None })
+	}
+
+	pub fn reset_hour(&self) -> u32
+	{
+		self.defaults.reset_hour
+	}
+
+	/// Size of the longest battle pass, seasons past the calendar included
+	pub fn max_battle_pass_levels(&self) -> Level
+	{
+		self.seasons.iter()
+			.map(|season| season.battle_pass_levels)
+			.fold(self.defaults.battle_pass_levels, std::cmp::max)
+	}
+
+	/// Replaces seasons with the same number and adds the rest
+	fn merge(&mut self, overrides: SeasonCalendar)
+	{
+		for season in overrides.seasons
+		{
+			self.seasons.retain(|existing| existing.number != season.number);
+			self.seasons.push(season);
+		}
+		self.seasons.sort_by_key(|season| season.start);
+	}
+
+	/// The season running at `time`, guessed from the season length when it is not in the calendar
+	pub fn season_at(&self, time: chrono::DateTime<chrono::Utc>) -> Season
+	{
+		if let Some(season) = self.seasons.iter().find(|season| season.start <= time && time < season.end)
+		{
+			return season.clone();
+		}
+		// Extrapolate from the last known season before `time`
+		let (mut number, mut start) = match self.seasons.iter().rev().find(|season| season.end <= time)
+		{
+			Some(season) => (season.number + 1, season.end),
+			None => match self.seasons.first()
+			{
+				Some(season) => return season.clone(), // Before the first known season
+				None => (1, *SEASON_ONE_START),
+			},
+		};
+		let season_length = chrono::Duration::days(SEASON_LENGTH as i64);
+		while start + season_length <= time
+		{
+			start += season_length;
+			number += 1;
+		}
+		// Don't run into the next known season
+		let mut end = start + season_length;
+		if let Some(next) = self.seasons.iter().find(|season| season.start > start)
+		{
+			end = std::cmp::min(end, next.start);
+		}
+		Season
+		{
+			number,
+			start,
+			end,
+			name: None,
+			battle_pass_levels: self.defaults.battle_pass_levels,
+			mythic_level: std::cmp::min(self.defaults.mythic_level, self.defaults.battle_pass_levels),
+		}
+	}
+}
+
+/// RFC 3339 timestamp, or a YYYY-MM-DD date starting at the reset hour (UTC)
+pub fn parse_season_date(date: &str, reset_hour: u32) -> Option<chrono::DateTime<chrono::Utc>>
+{
+	if let Ok(date) = chrono::DateTime::parse_from_rfc3339(date)
+	{
+		return Some(date.with_timezone(&chrono::Utc));
+	}
+	let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+	Some(chrono::Utc.from_utc_datetime(&date.and_hms_opt(reset_hour, 0, 0)?))
+}
+
+/// RFC 3339 timestamp, or the first reset on a YYYY-MM-DD date in the region
+pub fn parse_local_date(date: &str, reset_hour: u32, region: Region) -> Option<chrono::DateTime<chrono::Utc>>
+{
+	if let Ok(date) = chrono::DateTime::parse_from_rfc3339(date)
+	{
+		return Some(date.with_timezone(&chrono::Utc));
+	}
+	let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+	let start_of_day = region.start_of_day(date);
+	let reset = chrono::Utc.from_utc_datetime(&start_of_day.date_naive().and_hms_opt(reset_hour, 0, 0)?);
+	Some(if reset < start_of_day { reset + chrono::Duration::days(1) } else { reset })
+}
+
 lazy_static::lazy_static!
 {
-	pub static ref SEASON_ONE_START: chrono::DateTime<chrono::Utc> = chrono::Utc.with_ymd_and_hms(2022, 10, 4, 0, 0, 0).unwrap();
-	// pub static ref SEASON_TWO_START: chrono::DateTime<chrono::Utc> = chrono::Utc.with_ymd_and_hms(2022, 12, 6, 0, 0, 0).unwrap();
-	// pub static ref SEASON_THREE_START: chrono::DateTime<chrono::Utc> = chrono::Utc.with_ymd_and_hms(2023, 2, 7, 0, 0, 0).unwrap();
+	pub static ref SEASON_ONE_START: chrono::DateTime<chrono::Utc> = chrono::Utc.with_ymd_and_hms(2022, 10, 4, 18, 0, 0).unwrap();
+
+	// Tests shouldn't depend on the user's seasons.json
+	pub static ref SEASON_CALENDAR: SeasonCalendar = if cfg!(test) { SeasonCalendar::bundled() } else { SeasonCalendar::load() };
+}
+
+pub fn get_current_season(clock: &dyn Clock) -> Season
+{
+	SEASON_CALENDAR.season_at(clock.now())
+}
+
+/// When the target has to be reached, the personal deadline if it falls inside the current season, otherwise the season end
+pub fn get_goal_end(clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> chrono::DateTime<chrono::Utc>
+{
+	let season = get_current_season(clock);
+	match deadline
+	{
+		// Deadlines from earlier seasons are stale, later ones don't matter yet
+		Some(deadline) if season.start < deadline && deadline < season.end => deadline,
+		_ => season.end,
+	}
+}
+
+/// Calendar days in the region until the day of the goal end
+pub fn get_remaining_days(clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> i64
+{
+	(region.local_date(get_goal_end(clock, deadline)) - region.local_date(clock.now())).num_days()
+}
+
+/// Weekday weekly challenges reset on, at the calendar's reset hour
+pub const WEEKLY_RESET_DAY: chrono::Weekday = chrono::Weekday::Tue;
+
+/// Weekly resets after `from` and before `to`
+pub fn weekly_resets_between(from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>, reset_hour: u32) -> u32
+{
+	let date = from.date_naive();
+	let days_until_reset = (7 + WEEKLY_RESET_DAY.num_days_from_monday() - chrono::Datelike::weekday(&date).num_days_from_monday()) % 7;
+	let mut reset = chrono::Utc.from_utc_datetime(&(date + chrono::Duration::days(days_until_reset as i64)).and_hms_opt(reset_hour, 0, 0).unwrap());
+	if reset <= from
+	{
+		reset += chrono::Duration::weeks(1);
+	}
+	let mut resets = 0;
+	while reset < to
+	{
+		resets += 1;
+		reset += chrono::Duration::weeks(1);
+	}
+	resets
+}
+
+/// Weekly resets left before the goal end
+pub fn get_weekly_resets_left(clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> u32
+{
+	weekly_resets_between(clock.now(), get_goal_end(clock, deadline), SEASON_CALENDAR.reset_hour())
+}
+
+/// Exact time left until the goal end
+pub fn get_remaining_time(clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> chrono::Duration
+{
+	get_goal_end(clock, deadline) - clock.now()
+}
 
-	pub static ref CURRENT_SEASON_START: chrono::DateTime<chrono::Utc> = get_current_season_start();
-	pub static ref CURRENT_SEASON_NUMBER: u8 = get_current_season_number();
+/// Time left until the goal end in days, fractions included
+pub fn get_fractional_remaining_days(clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> f64
+{
+	(get_remaining_time(clock, deadline).num_seconds() as f64 / 86400.0).max(0.0)
 }
 
-fn get_current_season_start() -> chrono::DateTime<chrono::Utc>
+/// Time left split into days, hours and minutes for the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Countdown
 {
-	let mut season_start = *SEASON_ONE_START;
-	while chrono::Utc::now() > season_start && season_start < chrono::Utc::now() + chrono::Duration::days(SEASON_LENGTH as i64)
+	pub days: i64,
+	pub hours: i64,
+	pub minutes: i64,
+}
+
+impl From<chrono::Duration> for Countdown
+{
+	fn from(remaining: chrono::Duration) -> Self
 	{
-		season_start = season_start + chrono::Duration::days(SEASON_LENGTH as i64);
+		let minutes = std::cmp::max(remaining.num_minutes(), 0);
+		Self
+		{
+			days: minutes / (24 * 60),
+			hours: minutes / 60 % 24,
+			minutes: minutes % 60,
+		}
 	}
-	season_start = season_start - chrono::Duration::days(SEASON_LENGTH as i64); // Subtract one season length to get the start of the current season
-	// println!("Current season start: {}", season_start);
-	season_start
 }
 
-fn get_current_season_number() -> u8
+impl std::fmt::Display for Countdown
 {
-	let mut season_number = 1;
-	let mut season_start = *SEASON_ONE_START;
-	while chrono::Utc::now() > season_start && season_start < chrono::Utc::now() + chrono::Duration::days(SEASON_LENGTH as i64)
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
 	{
-		season_start = season_start + chrono::Duration::days(SEASON_LENGTH as i64);
-		season_number += 1;
+		write!(f, "{}d {}h {}m", self.days, self.hours, self.minutes)
 	}
-	season_number
 }
 
-pub fn get_levels_required_per_day(battle_pass_level: u8, battle_pass_target: u8) -> f64
+/// Level and XP into it along with the target level.
+/// The target is a battle pass level, the level never goes past the target and there is no XP left to earn at the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BattlePassProgress
+{
+	level: Level,
+	xp: u32,
+	target: Level,
+}
+
+impl BattlePassProgress
 {
-	let remaining_days = (SEASON_LENGTH as i64) + (get_current_season_start() - chrono::Utc::now()).num_days();
-	let remaining_levels = battle_pass_target - battle_pass_level;
-	let levels_required_per_day = remaining_levels as f64 / remaining_days as f64;
-	if levels_required_per_day < 0.0
+	/// Clamps every value into range instead of failing
+	pub fn new(level: Level, xp: u32, target: Level) -> Self
+	{
+		let target = std::cmp::max(target, 1);
+		let level = std::cmp::min(level, target);
+		let xp = if level == target { 0 } else { std::cmp::min(xp, XP_PER_LEVEL - 1) };
+		Self { level, xp, target }
+	}
+
+	pub fn level(self) -> Level
+	{
+		self.level
+	}
+
+	/// XP into the current level
+	pub fn xp(self) -> u32
+	{
+		self.xp
+	}
+
+	pub fn target(self) -> Level
+	{
+		self.target
+	}
+
+	pub fn with_level(self, level: Level) -> Self
+	{
+		Self::new(level, self.xp, self.target)
+	}
+
+	pub fn with_xp(self, xp: u32) -> Self
+	{
+		Self::new(self.level, xp, self.target)
+	}
+
+	/// Lowering the target below the level brings the level down with it
+	pub fn with_target(self, target: Level) -> Self
+	{
+		Self::new(self.level, self.xp, target)
+	}
+
+	pub fn increment(self) -> Self
+	{
+		self.with_level(self.level.saturating_add(1))
+	}
+
+	pub fn decrement(self) -> Self
+	{
+		self.with_level(self.level.saturating_sub(1))
+	}
+
+	/// XP earned since the start of the battle pass
+	pub fn total_xp(self) -> u32
+	{
+		self.level as u32 * XP_PER_LEVEL + self.xp
+	}
+
+	/// XP still needed to reach the target
+	pub fn remaining_xp(self) -> u32
+	{
+		self.target as u32 * XP_PER_LEVEL - self.total_xp()
+	}
+
+	/// Progress in levels with the XP as a fraction
+	pub fn fractional_level(self) -> f64
+	{
+		self.total_xp() as f64 / XP_PER_LEVEL as f64
+	}
+
+	pub fn is_complete(self) -> bool
+	{
+		self.level == self.target
+	}
+
+	/// Progress after earning some XP, stopping at the target
+	pub fn add_xp(self, xp: u32) -> Self
+	{
+		let total_xp = std::cmp::min(self.total_xp().saturating_add(xp), self.target as u32 * XP_PER_LEVEL);
+		Self::new((total_xp / XP_PER_LEVEL) as Level, total_xp % XP_PER_LEVEL, self.target)
+	}
+}
+
+/// Shown everywhere progress is summed up once the target is reached
+pub const TARGET_REACHED: &str = "You have reached your target! Congratulations!";
+
+/// Remaining XP spread over the time left, the last day counting as the fraction of it that's left
+pub fn get_xp_required_per_day(progress: BattlePassProgress, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> f64
+{
+	let remaining_days = get_fractional_remaining_days(clock, deadline);
+	let remaining_xp = progress.remaining_xp();
+	if remaining_xp == 0
 	{
 		0.0
 	}
+	else if remaining_days > 0.0
+	{
+		remaining_xp as f64 / remaining_days
+	}
+	else
+	{
+		remaining_xp as f64 // The season is over, all of it would be needed now
+	}
+}
+
+/// Weekdays the player plays on, minus one-off days off like holidays
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PlaySchedule
+{
+	pub weekdays: Vec<chrono::Weekday>,
+	pub days_off: Vec<chrono::NaiveDate>,
+}
+
+pub const WEEKDAYS: [chrono::Weekday; 7] = [chrono::Weekday::Mon, chrono::Weekday::Tue, chrono::Weekday::Wed, chrono::Weekday::Thu, chrono::Weekday::Fri, chrono::Weekday::Sat, chrono::Weekday::Sun];
+
+impl Default for PlaySchedule
+{
+	fn default() -> Self
+	{
+		Self { weekdays: WEEKDAYS.to_vec(), days_off: Vec::new() }
+	}
+}
+
+impl PlaySchedule
+{
+	pub fn plays_on(&self, date: chrono::NaiveDate) -> bool
+	{
+		use chrono::Datelike;
+		self.weekdays.contains(&date.weekday()) && !self.days_off.contains(&date)
+	}
+
+	/// Play days left until the goal end, today and the last day counting as the fraction of them that's left
+	pub fn remaining_play_days(&self, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> f64
+	{
+		self.play_days_between(clock.now(), get_goal_end(clock, deadline), region)
+	}
+
+	/// Play days between from and to, partial days counting as the fraction of them inside
+	fn play_days_between(&self, from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>, region: Region) -> f64
+	{
+		let mut date = region.local_date(from);
+		let mut play_days = 0.0;
+		while date <= region.local_date(to)
+		{
+			let next = date.succ_opt().unwrap();
+			if self.plays_on(date)
+			{
+				let start = std::cmp::max(region.start_of_day(date), from);
+				let end = std::cmp::min(region.start_of_day(next), to);
+				play_days += ((end - start).num_seconds() as f64 / 86400.0).max(0.0);
+			}
+			date = next;
+		}
+		play_days
+	}
+}
+
+/// Remaining XP spread over the play days left, None when the schedule has none
+pub fn get_xp_required_per_play_day(progress: BattlePassProgress, schedule: &PlaySchedule, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> Option<f64>
+{
+	let play_days = schedule.remaining_play_days(clock, deadline, region);
+	if progress.is_complete()
+	{
+		Some(0.0)
+	}
+	else if play_days > 0.0
+	{
+		Some(progress.remaining_xp() as f64 / play_days)
+	}
 	else
 	{
-		levels_required_per_day
+		None
+	}
+}
+
+/// How the observed play rate is estimated from the levels gained each day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaceEstimator
+{
+	OverallAverage,
+	#[default]
+	LastSevenDays,
+	ExponentiallyWeighted,
+}
+pub const PACE_ESTIMATORS: [PaceEstimator; 3] = [PaceEstimator::OverallAverage, PaceEstimator::LastSevenDays, PaceEstimator::ExponentiallyWeighted];
+
+impl std::fmt::Display for PaceEstimator
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			PaceEstimator::OverallAverage => write!(f, "Season average"),
+			PaceEstimator::LastSevenDays => write!(f, "Last 7 days"),
+			PaceEstimator::ExponentiallyWeighted => write!(f, "Recent days weighted"),
+		}
+	}
+}
+
+// Weight lost per day of age by the exponentially weighted estimator
+const EXPONENTIAL_DECAY: f64 = 0.3;
+// Width of the confidence band in standard errors, about 95%
+const CONFIDENCE_Z: f64 = 1.96;
+
+impl PaceEstimator
+{
+	/// Weight of each day, oldest first
+	fn weights(self, days: usize) -> Vec<f64>
+	{
+		(0..days)
+			.map(|day| match self
+			{
+				PaceEstimator::OverallAverage => 1.0,
+				PaceEstimator::LastSevenDays => if days - day <= 7 { 1.0 } else { 0.0 },
+				PaceEstimator::ExponentiallyWeighted => (1.0 - EXPONENTIAL_DECAY).powi((days - day - 1) as i32),
+			})
+			.collect()
+	}
+}
+
+/// Where the observed play rate leads by the goal end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection
+{
+	pub levels_per_day: f64,
+	pub completion: Option<chrono::DateTime<chrono::Utc>>, // None when no progress is being made
+	// Confidence band for the completion date
+	pub earliest_completion: Option<chrono::DateTime<chrono::Utc>>,
+	pub latest_completion: Option<chrono::DateTime<chrono::Utc>>,
+	pub level_at_goal_end: f64,
+}
+
+impl Projection
+{
+	/// Levels still missing at the goal end, 0 when the target will be reached
+	pub fn shortfall(&self, battle_pass_target: Level) -> Level
+	{
+		(battle_pass_target as f64 - self.level_at_goal_end).ceil().clamp(0.0, Level::MAX as f64) as Level
+	}
+}
+
+/// Projects when the target is reached from the levels gained on each day of the season so far, oldest first
+pub fn project_completion(daily_gains: &[f64], progress: BattlePassProgress, estimator: PaceEstimator, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>) -> Projection
+{
+	let weights = estimator.weights(daily_gains.len());
+	let weight_sum: f64 = weights.iter().sum();
+	let (levels_per_day, standard_error) = if weight_sum > 0.0
+	{
+		let mean = daily_gains.iter().zip(&weights).map(|(gain, weight)| gain * weight).sum::<f64>() / weight_sum;
+		let variance = daily_gains.iter().zip(&weights).map(|(gain, weight)| weight * (gain - mean).powi(2)).sum::<f64>() / weight_sum;
+		let effective_days = weight_sum.powi(2) / weights.iter().map(|weight| weight * weight).sum::<f64>();
+		(mean, (variance / effective_days).sqrt())
+	}
+	else
+	{
+		(0.0, 0.0)
+	};
+
+	let now = clock.now();
+	let remaining_levels = progress.remaining_xp() as f64 / XP_PER_LEVEL as f64;
+	let completion_at = |levels_per_day: f64|
+	{
+		if remaining_levels == 0.0
+		{
+			Some(now)
+		}
+		else if levels_per_day > 0.0
+		{
+			Some(now + chrono::Duration::seconds((remaining_levels / levels_per_day * 86400.0) as i64))
+		}
+		else
+		{
+			None
+		}
+	};
+	let remaining_days = get_fractional_remaining_days(clock, deadline);
+	Projection
+	{
+		levels_per_day,
+		completion: completion_at(levels_per_day),
+		earliest_completion: completion_at(levels_per_day + CONFIDENCE_Z * standard_error),
+		latest_completion: completion_at(levels_per_day - CONFIDENCE_Z * standard_error),
+		level_at_goal_end: progress.fractional_level() + levels_per_day * remaining_days,
+	}
+}
+
+/// A limited time XP event such as a double XP weekend, the end is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BoostWindow
+{
+	pub start: chrono::DateTime<chrono::Utc>,
+	pub end: chrono::DateTime<chrono::Utc>,
+	pub multiplier: f64, // 2.0 for double XP
+}
+
+impl BoostWindow
+{
+	/// Play days of the window that fall between from and to
+	fn overlap_days(&self, from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>, schedule: &PlaySchedule, region: Region) -> f64
+	{
+		schedule.play_days_between(std::cmp::max(self.start, from), std::cmp::min(self.end, to), region)
+	}
+}
+
+/// Hours of play needed to reach the target once boosts are counted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayTime
+{
+	pub hours_per_day: f64,
+	pub total_hours: f64,
+	pub boost_days: f64, // Days left in boost windows
+	pub boost_windows_xp: f64, // XP from playing the daily maximum during boost windows only
+}
+
+impl PlayTime
+{
+	/// Whether playing only during the remaining boost windows reaches the target
+	pub fn boost_windows_are_enough(&self, remaining_xp: u32) -> bool
+	{
+		self.boost_days > 0.0 && self.boost_windows_xp >= remaining_xp as f64
+	}
+}
+
+/// Spreads the remaining XP over the play days until the goal end, premium_boost being the fraction added by the premium pass.
+/// Boosts add up, so premium during double XP earns 2.2 times the base XP
+#[allow(clippy::too_many_arguments)]
+pub fn get_required_play_time(remaining_xp: u32, xp_per_hour: u32, premium_boost: f64, max_hours_per_day: f64, boost_windows: &[BoostWindow], schedule: &PlaySchedule, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> Option<PlayTime>
+{
+	let now = clock.now();
+	let goal_end = get_goal_end(clock, deadline);
+	let remaining_days = schedule.play_days_between(now, goal_end, region);
+	if xp_per_hour == 0 || remaining_days <= 0.0
+	{
+		return None;
+	}
+	let boost_days: f64 = boost_windows.iter().map(|window| window.overlap_days(now, goal_end, schedule, region)).sum();
+	let extra_boost_days: f64 = boost_windows.iter().map(|window| window.overlap_days(now, goal_end, schedule, region) * (window.multiplier - 1.0)).sum();
+	let weighted_days = remaining_days * (1.0 + premium_boost) + extra_boost_days;
+	let hours_per_day = remaining_xp as f64 / (xp_per_hour as f64 * weighted_days);
+	let boost_windows_xp = boost_windows.iter()
+		.map(|window| window.overlap_days(now, goal_end, schedule, region) * (window.multiplier + premium_boost))
+		.sum::<f64>() * max_hours_per_day * xp_per_hour as f64;
+	Some(PlayTime
+	{
+		hours_per_day,
+		total_hours: hours_per_day * remaining_days,
+		boost_days,
+		boost_windows_xp,
+	})
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::clock::FixedClock;
+
+	const UTC: Region = Region::Zone(chrono_tz::UTC);
+
+	fn at(year: i32, month: u32, day: u32, hour: u32) -> FixedClock
+	{
+		FixedClock(chrono::Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap())
+	}
+
+	#[test]
+	fn local_dates_resolve_to_the_reset_on_that_day()
+	{
+		let reset = |hour| chrono::Utc.with_ymd_and_hms(2023, 1, 20, hour, 0, 0).unwrap();
+		assert_eq!(parse_local_date("2023-01-20", 18, UTC), Some(reset(18)));
+		// 10:00 in Los Angeles, while in Tokyo the reset on the 20th is at 03:00, the evening before in UTC
+		assert_eq!(parse_local_date("2023-01-20", 18, Region::Zone(chrono_tz::America::Los_Angeles)), Some(reset(18)));
+		assert_eq!(parse_local_date("2023-01-20", 18, Region::Zone(chrono_tz::Asia::Tokyo)), Some(reset(18) - chrono::Duration::days(1)));
+		assert_eq!(parse_local_date("2023-01-20T12:00:00Z", 18, UTC), Some(reset(12)));
+		assert_eq!(parse_local_date("20/01/2023", 18, UTC), None);
+	}
+
+	#[test]
+	fn season_start_and_number_inside_listed_season()
+	{
+		let season = get_current_season(&at(2023, 1, 1, 12));
+		assert_eq!(season.number, 2);
+		assert_eq!(season.start, chrono::Utc.with_ymd_and_hms(2022, 12, 6, 18, 0, 0).unwrap());
+		assert_eq!(season.end, chrono::Utc.with_ymd_and_hms(2023, 2, 7, 18, 0, 0).unwrap());
+	}
+
+	#[test]
+	fn season_boundary_belongs_to_new_season()
+	{
+		let before = FixedClock(chrono::Utc.with_ymd_and_hms(2022, 12, 6, 17, 59, 59).unwrap());
+		assert_eq!(get_current_season(&before).number, 1);
+		assert_eq!(get_current_season(&at(2022, 12, 6, 18)).number, 2);
+	}
+
+	#[test]
+	fn irregular_season_lengths_come_from_calendar()
+	{
+		// Season 5 was shorter than 63 days
+		let season = get_current_season(&at(2023, 8, 9, 0));
+		assert_eq!(season.number, 5);
+		assert_eq!(get_current_season(&at(2023, 8, 10, 18)).number, 6);
+		assert_eq!(get_current_season(&at(2023, 8, 10, 18)).name.as_deref(), Some("Invasion"));
+	}
+
+	#[test]
+	fn unlisted_seasons_fall_back_to_season_length()
+	{
+		let last_listed_end = chrono::Utc.with_ymd_and_hms(2025, 2, 18, 18, 0, 0).unwrap();
+		let season = get_current_season(&FixedClock(last_listed_end));
+		assert_eq!(season.number, 15);
+		assert_eq!(season.start, last_listed_end);
+		assert_eq!(season.end - season.start, chrono::Duration::days(SEASON_LENGTH as i64));
+
+		let two_seasons_later = last_listed_end + chrono::Duration::days(2 * SEASON_LENGTH as i64 + 1);
+		let season = get_current_season(&FixedClock(two_seasons_later));
+		assert_eq!(season.number, 17);
+		assert_eq!(season.start, last_listed_end + chrono::Duration::days(2 * SEASON_LENGTH as i64));
+	}
+
+	#[test]
+	fn battle_pass_size_comes_from_season_data()
+	{
+		let calendar = SeasonCalendar::from_json(r#"{
+			"battle_pass_levels": 200,
+			"seasons":
+			[
+				{ "number": 1, "start": "2030-01-01", "end": "2030-03-01" },
+				{ "number": 2, "start": "2030-03-01", "end": "2030-05-01", "battle_pass_levels": 300, "mythic_level": 120 }
+			]
+		}"#, SeasonDefaults::default()).unwrap();
+		let first = calendar.season_at(at(2030, 2, 1, 0).0);
+		let second = calendar.season_at(at(2030, 4, 1, 0).0);
+		assert_eq!((first.battle_pass_levels, first.mythic_level), (200, 80));
+		assert_eq!((second.battle_pass_levels, second.mythic_level), (300, 120));
+		assert_eq!(BattlePassTarget::Prestige.level(&second), 300);
+		assert_eq!(BattlePassTarget::Mythic.level(&second), 120);
+		assert_eq!(BattlePassTarget::Custom { level: 250 }.level(&first), 200);
+		assert_eq!(BattlePassTarget::Custom { level: 250 }.level(&second), 250);
+		// Guessed seasons use the file's sizes
+		assert_eq!(calendar.season_at(at(2030, 6, 1, 0).0).battle_pass_levels, 200);
+		assert_eq!(calendar.max_battle_pass_levels(), 300);
+	}
+
+	#[test]
+	fn before_first_season_uses_season_one()
+	{
+		assert_eq!(get_current_season(&at(2022, 1, 1, 0)).number, 1);
+	}
+
+	#[test]
+	fn remaining_days_count_down_to_season_end()
+	{
+		assert_eq!(get_remaining_days(&at(2023, 1, 1, 0), None, UTC), 37);
+		assert_eq!(get_remaining_days(&at(2023, 2, 6, 0), None, UTC), 1);
+		assert_eq!(get_remaining_days(&at(2023, 2, 7, 17), None, UTC), 0);
+		assert_eq!(get_remaining_days(&at(2023, 2, 7, 18), None, UTC), 63); // Season 3 just started
+	}
+
+	#[test]
+	fn remaining_days_follow_local_midnight()
+	{
+		// 23:00 on the 7th in Shanghai, an hour before the last day there
+		let clock = at(2023, 2, 7, 15);
+		assert_eq!(get_remaining_days(&clock, None, UTC), 0);
+		assert_eq!(get_remaining_days(&clock, None, Region::Zone(chrono_tz::Asia::Shanghai)), 1);
+		assert_eq!(get_remaining_days(&clock, None, Region::Zone(chrono_tz::America::Los_Angeles)), 0);
+	}
+
+	#[test]
+	fn xp_required_per_day_spreads_remaining_xp()
+	{
+		let clock = at(2023, 1, 1, 18); // 37 days left
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(0, 0, 74), &clock, None), 20_000.0);
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(37, 0, 74), &clock, None), 10_000.0);
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(36, 6_300, 74), &clock, None), 10_100.0);
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(80, 0, 80), &clock, None), 0.0);
+		// Fractional days count too
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(0, 0, 74), &at(2023, 1, 2, 6), None), 740_000.0 / 36.5);
+		assert_eq!(BattlePassProgress::new(79, 2_500, 80).remaining_xp(), 7_500);
+	}
+
+	#[test]
+	fn projection_estimators_weigh_days_differently()
+	{
+		let clock = at(2023, 1, 1, 0); // 37 days left
+		let gains = [0.0, 0.0, 0.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0];
+		let overall = project_completion(&gains, BattlePassProgress::new(20, 0, 80), PaceEstimator::OverallAverage, &clock, None);
+		let last_week = project_completion(&gains, BattlePassProgress::new(20, 0, 80), PaceEstimator::LastSevenDays, &clock, None);
+		let weighted = project_completion(&gains, BattlePassProgress::new(20, 0, 80), PaceEstimator::ExponentiallyWeighted, &clock, None);
+		assert!((overall.levels_per_day - 2.8).abs() < 1e-9);
+		assert_eq!(last_week.levels_per_day, 4.0);
+		assert!(weighted.levels_per_day > overall.levels_per_day && weighted.levels_per_day < 4.0);
+
+		// A steady rate has no spread, 60 levels at 4 a day takes 15 days
+		assert_eq!(last_week.completion, Some(clock.0 + chrono::Duration::days(15)));
+		assert_eq!(last_week.earliest_completion, last_week.completion);
+		assert_eq!(last_week.shortfall(80), 0);
+		// A noisy rate widens the band
+		assert!(overall.earliest_completion < overall.completion && overall.completion < overall.latest_completion);
+	}
+
+	#[test]
+	fn projection_reports_shortfall()
+	{
+		let clock = at(2023, 1, 1, 0); // 37 days left
+		let projection = project_completion(&[1.0; 5], BattlePassProgress::new(0, 5_000, 80), PaceEstimator::OverallAverage, &clock, None);
+		assert_eq!(projection.level_at_goal_end, 38.25); // The season ends at 18:00
+		assert_eq!(projection.shortfall(80), 42);
+		assert_eq!(project_completion(&[], BattlePassProgress::new(0, 0, 80), PaceEstimator::LastSevenDays, &clock, None).completion, None);
+	}
+
+	#[test]
+	fn xp_required_per_day_resets_across_season_boundary()
+	{
+		let day_before = get_xp_required_per_day(BattlePassProgress::new(0, 0, 63), &at(2023, 2, 6, 18), None);
+		let last_hours = get_xp_required_per_day(BattlePassProgress::new(0, 0, 63), &at(2023, 2, 7, 6), None); // Used to divide by 0 days
+		let first_day = get_xp_required_per_day(BattlePassProgress::new(0, 0, 63), &at(2023, 2, 7, 18), None);
+		assert_eq!(day_before, 630_000.0);
+		assert_eq!(last_hours, 1_260_000.0);
+		assert_eq!(first_day, 10_000.0);
+	}
+
+	#[test]
+	fn pacing_only_counts_scheduled_play_days()
+	{
+		// Saturday noon, season 2 ends on Tuesday February 7 at 18:00
+		let clock = at(2023, 1, 28, 12);
+		let every_day = PlaySchedule::default();
+		assert!((every_day.remaining_play_days(&clock, None, UTC) - get_fractional_remaining_days(&clock, None)).abs() < 1e-9);
+
+		// Half of today, Sunday and the next weekend
+		let mut weekends = PlaySchedule { weekdays: vec![chrono::Weekday::Sat, chrono::Weekday::Sun], days_off: Vec::new() };
+		assert!((weekends.remaining_play_days(&clock, None, UTC) - 3.5).abs() < 1e-9);
+		weekends.days_off.push(chrono::NaiveDate::from_ymd_opt(2023, 2, 4).unwrap());
+		assert!((weekends.remaining_play_days(&clock, None, UTC) - 2.5).abs() < 1e-9);
+
+		let progress = BattlePassProgress::new(10, 0, 15);
+		assert_eq!(get_xp_required_per_play_day(progress, &weekends, &clock, None, UTC), Some(20_000.0));
+		let never = PlaySchedule { weekdays: Vec::new(), days_off: Vec::new() };
+		assert_eq!(get_xp_required_per_play_day(progress, &never, &clock, None, UTC), None);
+	}
+
+	#[test]
+	fn earlier_deadline_drives_pacing()
+	{
+		let clock = at(2023, 1, 1, 18); // 37 days left in season 2
+		let deadline = Some(at(2023, 1, 11, 18).0);
+		assert_eq!(get_goal_end(&clock, deadline), at(2023, 1, 11, 18).0);
+		assert_eq!(get_remaining_days(&clock, deadline, UTC), 10);
+		assert_eq!(get_xp_required_per_day(BattlePassProgress::new(0, 0, 74), &clock, deadline), 74_000.0);
+		assert!((PlaySchedule::default().remaining_play_days(&clock, deadline, UTC) - 10.0).abs() < 1e-9);
+
+		// Deadlines after the season end or from an earlier season leave the season end in charge
+		let season_end = get_current_season(&clock).end;
+		assert_eq!(get_goal_end(&clock, Some(at(2023, 3, 1, 18).0)), season_end);
+		assert_eq!(get_goal_end(&clock, Some(at(2022, 11, 1, 18).0)), season_end);
+		assert_eq!(get_goal_end(&clock, None), season_end);
+	}
+
+	#[test]
+	fn countdown_splits_remaining_time()
+	{
+		let countdown = Countdown::from(get_remaining_time(&FixedClock(chrono::Utc.with_ymd_and_hms(2023, 2, 5, 15, 29, 30).unwrap()), None));
+		assert_eq!(countdown, Countdown { days: 2, hours: 2, minutes: 30 });
+		assert_eq!(countdown.to_string(), "2d 2h 30m");
+		assert_eq!(Countdown::from(chrono::Duration::seconds(-5)), Countdown { days: 0, hours: 0, minutes: 0 });
+	}
+
+	#[test]
+	fn boosts_reduce_required_play_time()
+	{
+		let clock = at(2023, 1, 1, 18); // 37 days left
+		let every_day = PlaySchedule::default();
+		let plain = get_required_play_time(370_000, 1_000, 0.0, 4.0, &[], &every_day, &clock, None, UTC).unwrap();
+		assert!((plain.hours_per_day - 10.0).abs() < 1e-9);
+		assert!((plain.total_hours - 370.0).abs() < 1e-9);
+		assert!(!plain.boost_windows_are_enough(370_000));
+
+		// Premium and a double XP weekend that started before now, only the part left counts
+		let weekend = BoostWindow { start: at(2022, 12, 31, 18).0, end: at(2023, 1, 3, 18).0, multiplier: 2.0 };
+		let boosted = get_required_play_time(370_000, 1_000, 0.2, 4.0, &[weekend], &every_day, &clock, None, UTC).unwrap();
+		assert!((boosted.hours_per_day - 370.0 / (37.0 * 1.2 + 2.0)).abs() < 1e-9);
+		assert_eq!(boosted.boost_days, 2.0);
+		assert!((boosted.boost_windows_xp - 2.0 * 2.2 * 4.0 * 1_000.0).abs() < 1e-9);
+		assert!(boosted.boost_windows_are_enough(17_600));
+		assert!(!boosted.boost_windows_are_enough(17_601));
+		assert_eq!(get_required_play_time(370_000, 0, 0.2, 4.0, &[weekend], &every_day, &clock, None, UTC), None);
+
+		// Only weekends left: the rest of this Sunday and ten more weekend days, a quarter of a day of them boosted
+		let weekends = PlaySchedule { weekdays: vec![chrono::Weekday::Sat, chrono::Weekday::Sun], days_off: Vec::new() };
+		let weekend_play = get_required_play_time(370_000, 1_000, 0.0, 4.0, &[weekend], &weekends, &clock, None, UTC).unwrap();
+		assert!((weekend_play.hours_per_day - 370.0 / (10.25 + 0.25)).abs() < 1e-9);
+		assert!((weekend_play.total_hours - 370.0 / 10.5 * 10.25).abs() < 1e-9);
+		assert_eq!(weekend_play.boost_days, 0.25);
+	}
+
+	// XP values around the edges of a level
+	const XP_SAMPLES: [u32; 6] = [0, 1, XP_PER_LEVEL / 2, XP_PER_LEVEL - 1, XP_PER_LEVEL, u32::MAX];
+
+	// Every level up to a bit past the longest battle pass so far, and the extremes
+	fn level_samples() -> impl Iterator<Item = Level> + Clone
+	{
+		(0..=300).chain([Level::MAX - 1, Level::MAX])
+	}
+
+	fn all_progress() -> impl Iterator<Item = (Level, u32, Level)>
+	{
+		level_samples().flat_map(|level| XP_SAMPLES.into_iter().flat_map(move |xp| level_samples().map(move |target| (level, xp, target))))
+	}
+
+	fn assert_valid(progress: BattlePassProgress)
+	{
+		assert!(progress.target() >= 1, "{:?}", progress);
+		assert!(progress.level() <= progress.target(), "{:?}", progress);
+		assert!(progress.xp() < XP_PER_LEVEL, "{:?}", progress);
+		assert!(!progress.is_complete() || progress.xp() == 0, "{:?}", progress);
+		assert_eq!(progress.total_xp() + progress.remaining_xp(), progress.target() as u32 * XP_PER_LEVEL);
+	}
+
+	#[test]
+	fn progress_stays_valid_for_every_input()
+	{
+		for (level, xp, target) in all_progress()
+		{
+			let progress = BattlePassProgress::new(level, xp, target);
+			assert_valid(progress);
+			// Values already in range are kept
+			if target >= 1 && level < target && xp < XP_PER_LEVEL
+			{
+				assert_eq!((progress.level(), progress.xp(), progress.target()), (level, xp, target));
+			}
+			assert_valid(progress.increment());
+			assert_valid(progress.decrement());
+			assert_valid(progress.with_target(level));
+			assert_valid(progress.with_xp(xp));
+		}
+	}
+
+	#[test]
+	fn progress_steps_are_clamped()
+	{
+		for (level, xp, target) in all_progress()
+		{
+			let progress = BattlePassProgress::new(level, xp, target);
+			assert_eq!(progress.increment().level(), std::cmp::min(progress.level().saturating_add(1), progress.target()));
+			assert_eq!(progress.decrement().level(), progress.level().saturating_sub(1));
+			assert!(progress.with_target(level).level() <= progress.level());
+			assert_valid(progress.add_xp(xp));
+			assert!(progress.add_xp(u32::MAX).is_complete());
+			assert_eq!(progress.add_xp(XP_PER_LEVEL).total_xp(), std::cmp::min(progress.total_xp() + XP_PER_LEVEL, progress.target() as u32 * XP_PER_LEVEL));
+			let required = get_xp_required_per_day(progress, &at(2023, 1, 1, 18), None);
+			assert!(required.is_finite() && required >= 0.0);
+			assert_eq!(required == 0.0, progress.is_complete());
+		}
 	}
 }
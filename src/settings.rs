@@ -1,34 +1,169 @@
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
 
 use crate::seasons;
 
 
+/// Version written to new settings files, bump it and add a migration when the layout changes
+pub const SETTINGS_VERSION: u64 = 2;
+
+/*
+Example file:
+{
+	"version": 2,
+	"battle_pass":
+	{
+		"level": 0,
+		"xp": 2500,
+		"target": { "kind": "custom", "level": 120 },
+		"season": 3,
+		"premium": true,
+		"deadline": "2023-01-20T18:00:00Z"
+	},
+	"boost_windows":
+	[
+		{ "start": "2023-01-13T18:00:00Z", "end": "2023-01-16T18:00:00Z", "multiplier": 2.0 }
+	],
+	"play_schedule":
+	{
+		"weekdays": ["Fri", "Sat", "Sun"],
+		"days_off": ["2022-12-25"]
+	},
+	"season_archive":
+	[
+		{ "season": 2, "level": 80, "target": 80 }
+	],
+	"roll_mastery":
+	{
+		"tank": 0,
+		"damage": 0,
+		"support": 0,
+		"tank_goal": 0,
+		"damage_goal": 0,
+		"support_goal": 0
+	},
+	"planner":
+	{
+		"daily_challenge_xp": 6000,
+		"weekly_challenge_xp": 80000,
+		"win_xp": 900,
+		"loss_xp": 600,
+		"win_rate": 50,
+		"premium_boost": 20,
+		"xp_per_hour": 5000,
+		"max_hours_per_day": 4
+	},
+	"theme": "dark",
+	"pace_estimator": "last_seven_days",
+	"region": "Europe/Paris"
+}
+*/
+// Missing fields are filled in from Default so new fields never break existing files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OverHelperSettings
 {
-	pub battle_pass_level: u8,
-	pub battle_pass_target: u8,
+	pub version: u64,
+	pub battle_pass: BattlePassSettings,
+	pub boost_windows: Vec<seasons::BoostWindow>,
+	pub play_schedule: seasons::PlaySchedule,
+	pub season_archive: Vec<seasons::ArchivedSeason>,
+	pub roll_mastery: RollMasterySettings,
+	pub planner: PlannerSettings,
 
-	pub tank_wins: u8,
-	pub damage_wins: u8,
-	pub support_wins: u8,
+	pub theme: SupportedThemes,
+	pub pace_estimator: seasons::PaceEstimator,
+	pub region: crate::clock::Region, // Timezone days are counted in
+}
 
-	pub theme: iced::Theme,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BattlePassSettings
+{
+	pub level: seasons::Level,
+	pub xp: u32, // Into the current level
+	pub target: seasons::BattlePassTarget,
+	pub season: Option<u8>, // None for files written before seasons were tracked
+	pub premium: bool,
+	pub deadline: Option<chrono::DateTime<chrono::Utc>>, // Personal deadline, only used while it's earlier than the season end
 }
 
-pub fn settings_to_appstate(settings: &OverHelperSettings) -> crate::OverHelperApp
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RollMasterySettings
+{
+	pub tank: u8,
+	pub damage: u8,
+	pub support: u8,
+
+	pub tank_goal: u8,
+	pub damage_goal: u8,
+	pub support_goal: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlannerSettings
+{
+	pub daily_challenge_xp: u32,
+	pub weekly_challenge_xp: u32,
+	pub win_xp: u32,
+	pub loss_xp: u32,
+	pub win_rate: u32,
+	pub premium_boost: u32,
+	pub xp_per_hour: u32,
+	pub max_hours_per_day: u32,
+}
+
+pub fn settings_to_appstate(settings: &OverHelperSettings, clock: std::sync::Arc<dyn crate::clock::Clock>) -> crate::OverHelperApp
 {
 	crate::OverHelperApp
 	{
-		battle_pass_level: settings.battle_pass_level,
-		battle_pass_target: settings.battle_pass_target,
+		battle_pass: seasons::BattlePassProgress::new(settings.battle_pass.level, settings.battle_pass.xp, settings.battle_pass.target.level(&seasons::get_current_season(clock.as_ref()))),
+		battle_pass_target: settings.battle_pass.target,
+		season_number: settings.battle_pass.season.unwrap_or_else(|| seasons::get_current_season(clock.as_ref()).number),
+		battle_pass_premium: settings.battle_pass.premium,
+		battle_pass_deadline: settings.battle_pass.deadline,
+		boost_windows: settings.boost_windows.clone(),
+		play_schedule: settings.play_schedule.clone(),
+		season_archive: settings.season_archive.clone(),
+		new_season_banner: None,
+
+		history: crate::history::LevelHistory::default(), // Stored in its own file
+		profiles: crate::profiles::Profiles::default(), // Not part of a profile's settings, set by the caller
+		role_mastery: crate::role_mastery::RoleMastery
+		{
+			tank_wins: settings.roll_mastery.tank,
+			damage_wins: settings.roll_mastery.damage,
+			support_wins: settings.roll_mastery.support,
 
-		tank_wins: settings.tank_wins,
-		damage_wins: settings.damage_wins,
-		support_wins: settings.support_wins,
+			tank_goal: settings.roll_mastery.tank_goal,
+			damage_goal: settings.roll_mastery.damage_goal,
+			support_goal: settings.roll_mastery.support_goal,
+		},
+		planner: crate::planner::Planner
+		{
+			daily_challenge_xp: settings.planner.daily_challenge_xp,
+			weekly_challenge_xp: settings.planner.weekly_challenge_xp,
+			win_xp: settings.planner.win_xp,
+			loss_xp: settings.planner.loss_xp,
+			win_rate: std::cmp::min(settings.planner.win_rate, 100),
+			premium_boost: settings.planner.premium_boost,
+			xp_per_hour: settings.planner.xp_per_hour,
+			max_hours_per_day: std::cmp::min(settings.planner.max_hours_per_day, 24),
+		},
+		simulator: crate::simulator::Simulator::default(),
+		tab: crate::Tab::Tracker,
 
-		theme: settings.theme.clone(),
+		theme: settings.theme.into(),
+		pace_estimator: settings.pace_estimator,
+		region: settings.region,
 
 		settings_page: None,
+		settings_notice: None,
+		save_failed: false,
+
+		clock,
 	}
 }
 
@@ -36,25 +171,130 @@ pub fn appstate_to_settings(appstate: &crate::OverHelperApp) -> OverHelperSettin
 {
 	OverHelperSettings
 	{
-		battle_pass_level: appstate.battle_pass_level,
-		battle_pass_target: appstate.battle_pass_target,
+		version: SETTINGS_VERSION,
+		battle_pass: BattlePassSettings
+		{
+			level: appstate.battle_pass.level(),
+			xp: appstate.battle_pass.xp(),
+			target: appstate.battle_pass_target,
+			season: Some(appstate.season_number),
+			premium: appstate.battle_pass_premium,
+			deadline: appstate.battle_pass_deadline,
+		},
+		boost_windows: appstate.boost_windows.clone(),
+		play_schedule: appstate.play_schedule.clone(),
+		season_archive: appstate.season_archive.clone(),
+		roll_mastery: RollMasterySettings
+		{
+			tank: appstate.role_mastery.tank_wins,
+			damage: appstate.role_mastery.damage_wins,
+			support: appstate.role_mastery.support_wins,
+
+			tank_goal: appstate.role_mastery.tank_goal,
+			damage_goal: appstate.role_mastery.damage_goal,
+			support_goal: appstate.role_mastery.support_goal,
+		},
+		planner: PlannerSettings
+		{
+			daily_challenge_xp: appstate.planner.daily_challenge_xp,
+			weekly_challenge_xp: appstate.planner.weekly_challenge_xp,
+			win_xp: appstate.planner.win_xp,
+			loss_xp: appstate.planner.loss_xp,
+			win_rate: appstate.planner.win_rate,
+			premium_boost: appstate.planner.premium_boost,
+			xp_per_hour: appstate.planner.xp_per_hour,
+			max_hours_per_day: appstate.planner.max_hours_per_day,
+		},
+
+		theme: SupportedThemes::from(&appstate.theme),
+		pace_estimator: appstate.pace_estimator,
+		region: appstate.region,
+	}
+}
+
+#[derive(Debug)]
+pub enum SettingsError
+{
+	NoConfigDir,
+	Io
+	{
+		action: &'static str,
+		path: std::path::PathBuf,
+		source: std::io::Error,
+	},
+	Parse
+	{
+		path: std::path::PathBuf,
+		source: serde_json::Error,
+	},
+	Serialize
+	{
+		path: std::path::PathBuf,
+		source: serde_json::Error,
+	},
+}
 
-		tank_wins: appstate.tank_wins,
-		damage_wins: appstate.damage_wins,
-		support_wins: appstate.support_wins,
+impl std::fmt::Display for SettingsError
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			SettingsError::NoConfigDir => write!(f, "Could not find the config directory"),
+			SettingsError::Io { action, path, source } => write!(f, "Could not {} {}: {}", action, path.display(), source),
+			SettingsError::Parse { path, source } => write!(f, "Could not parse {}: {}", path.display(), source),
+			SettingsError::Serialize { path, source } => write!(f, "Could not prepare {} for saving: {}", path.display(), source),
+		}
+	}
+}
+
+impl std::error::Error for SettingsError
+{
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+	{
+		match self
+		{
+			SettingsError::NoConfigDir => None,
+			SettingsError::Io { source, .. } => Some(source),
+			SettingsError::Parse { source, .. } => Some(source),
+			SettingsError::Serialize { source, .. } => Some(source),
+		}
+	}
+}
 
-		theme: appstate.theme.clone(),
+impl SettingsError
+{
+	pub fn io(action: &'static str, path: &std::path::Path) -> impl FnOnce(std::io::Error) -> Self
+	{
+		let path = path.to_path_buf();
+		move |source| SettingsError::Io { action, path, source }
 	}
 }
 
-pub fn get_settings_path() -> std::path::PathBuf
+/// A settings problem shown on the main screen
+#[derive(Debug, Clone)]
+pub struct SettingsNotice
 {
-	let project_dirs = ProjectDirs::from("games", "partypurr", "OverHelper").expect("Could not find project directories");
+	pub message: String,
+	pub backup_path: Option<std::path::PathBuf>, // Copy of a corrupt settings file
+}
+
+pub fn get_config_dir() -> Result<std::path::PathBuf, SettingsError>
+{
+	let project_dirs = ProjectDirs::from("games", "partypurr", "OverHelper").ok_or(SettingsError::NoConfigDir)?;
 	// Assert that the config directory exists
-	std::fs::create_dir_all(project_dirs.config_dir()).expect("Could not create config directory");
+	std::fs::create_dir_all(project_dirs.config_dir()).map_err(SettingsError::io("create", project_dirs.config_dir()))?;
+	Ok(project_dirs.config_dir().to_path_buf())
+}
 
-	let settings_path = project_dirs.config_dir().join("settings.json"); // TODO: consider using a different file format
-	settings_path
+pub const SETTINGS_FILE_NAME: &str = "settings.json"; // TODO: consider using a different file format
+
+/// Settings and history are only written by release builds, so debug runs leave the real files alone
+pub const SAVES_FILES: bool = !cfg!(debug_assertions);
+
+pub fn get_settings_path(profile: &str) -> Result<std::path::PathBuf, SettingsError>
+{
+	Ok(crate::profiles::get_profile_dir(profile)?.join(SETTINGS_FILE_NAME))
 }
 
 impl Default for OverHelperSettings
@@ -63,123 +303,387 @@ impl Default for OverHelperSettings
 	{
 		Self
 		{
-			battle_pass_level: 0,
-			battle_pass_target: crate::seasons::PRESTIGE_BATTLE_PASS_END,
+			version: SETTINGS_VERSION,
+			battle_pass: BattlePassSettings::default(),
+			boost_windows: Vec::new(),
+			play_schedule: seasons::PlaySchedule::default(),
+			season_archive: Vec::new(),
+			roll_mastery: RollMasterySettings::default(),
+			planner: PlannerSettings::default(),
 
-			tank_wins: 0,
-			damage_wins: 0,
-			support_wins: 0,
+			theme: SupportedThemes::Dark,
+			pace_estimator: seasons::PaceEstimator::default(),
+			region: crate::clock::Region::System,
+		}
+	}
+}
 
-			theme: iced::Theme::Dark,
+impl Default for PlannerSettings
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			daily_challenge_xp: 6_000,
+			weekly_challenge_xp: 80_000,
+			win_xp: 900,
+			loss_xp: 600,
+			win_rate: 50,
+			premium_boost: 20,
+			xp_per_hour: 5_000,
+			max_hours_per_day: 4,
 		}
 	}
 }
 
-// These allow statements are needed because the settings file is not written in debug mode
-#[allow(unreachable_code)]
-#[allow(unused_variables)]
-pub fn write_settings(settings: &OverHelperSettings)
+impl Default for BattlePassSettings
 {
-	// Don't write settings if in debug profile
-	#[cfg(debug_assertions)]
+	fn default() -> Self
 	{
-		return;
+		Self
+		{
+			level: 0,
+			xp: 0,
+			target: seasons::BattlePassTarget::Prestige,
+			season: None,
+			premium: false,
+			deadline: None,
+		}
 	}
-	let settings_path = get_settings_path();
-	let mut file = std::fs::File::create(settings_path).expect("Could not create settings file");
-	use serde_json::json;
-	let battle_pass_target = if settings.battle_pass_target == crate::seasons::PRESTIGE_BATTLE_PASS_END { "prestige" }
-	else if settings.battle_pass_target == crate::seasons::MYTHIC_BATTLE_PASS_END { "mythic" }
-	else
+}
+
+type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+/// MIGRATIONS[n] upgrades a version n file to version n + 1
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] =
+[
+	migrate_unversioned,
+	migrate_tagged_target,
+];
+
+/// Upgrades a settings file of any older version to SETTINGS_VERSION
+pub fn migrate(mut settings: serde_json::Value) -> serde_json::Value
+{
+	let version = settings["version"].as_u64().unwrap_or(0); // Files before versioning have no version field
+	for migration in MIGRATIONS.iter().skip(version as usize)
 	{
-		// Custom level
-		let battle_pass_target = settings.battle_pass_target.to_string();
-		// Warning: this leaks memory
-		let battle_pass_target: &'static str = Box::leak(battle_pass_target.into_boxed_str());
-		battle_pass_target
+		settings = migration(settings);
+	}
+	settings
+}
+
+// Levels the named targets stood for when targets were stored as levels
+const LEGACY_PRESTIGE_LEVEL: u64 = 200;
+const LEGACY_MYTHIC_LEVEL: u64 = 80;
+
+/// Version 0 stored targets as "prestige", "mythic" or a custom level, sometimes as a string
+fn migrate_unversioned(mut settings: serde_json::Value) -> serde_json::Value
+{
+	let target = &settings["battle_pass"]["target"];
+	let target = match target.as_str()
+	{
+		Some("prestige") => Some(LEGACY_PRESTIGE_LEVEL),
+		Some("mythic") => Some(LEGACY_MYTHIC_LEVEL),
+		Some(custom) => custom.parse().ok(),
+		None => target.as_u64(),
 	};
-	// Construct JSON
-	let settings_json = json!
-	(
+	if let Some(battle_pass) = settings["battle_pass"].as_object_mut()
+	{
+		match target
 		{
-			"battle_pass":
-			{
-				"level": settings.battle_pass_level,
-				"target": battle_pass_target
-			},
-			"roll_mastery":
-			{
-				"tank": settings.tank_wins,
-				"damage": settings.damage_wins,
-				"support": settings.support_wins
-			}
+			Some(target) => { battle_pass.insert("target".to_string(), target.into()); },
+			None => { battle_pass.remove("target"); },
 		}
-	);
-	// Write JSON
-	serde_json::to_writer_pretty(&mut file, &settings_json).expect("Could not write settings file");
+	}
+	if let Some(settings) = settings.as_object_mut()
+	{
+		settings.insert("version".to_string(), 1.into());
+	}
+	settings
 }
 
-pub fn load_settings_or_default() -> OverHelperSettings
+/// Version 1 stored the target as a bare level, version 2 tags it with its kind
+fn migrate_tagged_target(mut settings: serde_json::Value) -> serde_json::Value
 {
-	let settings_path = get_settings_path();
-	if settings_path.exists()
+	if let Some(level) = settings["battle_pass"]["target"].as_u64()
 	{
-		// Load file
-		use serde_json::Value;
-		let file = std::fs::File::open(settings_path).expect("Could not open settings file");
-		let reader = std::io::BufReader::new(file);
-		let settings: Value = serde_json::from_reader(reader).expect("Could not parse settings file");
-		let mut result: OverHelperSettings = OverHelperSettings::default();
-		/*
-		Example file:
+		let target = match level
 		{
-			"battle_pass"
+			LEGACY_MYTHIC_LEVEL => serde_json::json!({ "kind": "mythic" }),
+			LEGACY_PRESTIGE_LEVEL => serde_json::json!({ "kind": "prestige" }),
+			_ => match seasons::Level::try_from(level).ok().and_then(|level| seasons::BattlePassTarget::custom(level).ok())
 			{
-				"level": 0,
-				"target": "prestige"
+				Some(_) => serde_json::json!({ "kind": "custom", "level": level }),
+				// Clearing the level box used to save 0, use the default target rather than failing the whole file
+				None => serde_json::json!({ "kind": "prestige" }),
 			},
-			"roll_mastery":
+		};
+		settings["battle_pass"]["target"] = target;
+	}
+	if let Some(settings) = settings.as_object_mut()
+	{
+		settings.insert("version".to_string(), 2.into());
+	}
+	settings
+}
+
+pub fn write_settings(profile: &str, settings: &OverHelperSettings) -> Result<(), SettingsError>
+{
+	write_settings_file(&get_settings_path(profile)?, settings)
+}
+
+pub fn write_settings_file(settings_path: &std::path::Path, settings: &OverHelperSettings) -> Result<(), SettingsError>
+{
+	let contents = serde_json::to_vec_pretty(settings).map_err(|source| SettingsError::Serialize { path: settings_path.to_path_buf(), source })?;
+	replace_settings_file(settings_path, &contents)
+}
+
+/// Number of previous settings files kept as settings.json.1 (newest) to settings.json.N
+pub const SETTINGS_BACKUP_COUNT: usize = 5;
+
+fn backup_path(settings_path: &std::path::Path, number: usize) -> std::path::PathBuf
+{
+	settings_path.with_file_name(format!("settings.json.{}", number))
+}
+
+/// Writes the settings file, keeping the previous one as a backup
+fn replace_settings_file(settings_path: &std::path::Path, contents: &[u8]) -> Result<(), SettingsError>
+{
+	// Don't write settings if in debug profile
+	if !SAVES_FILES
+	{
+		return Ok(());
+	}
+	rotate_and_replace_settings_file(settings_path, contents)
+}
+
+fn rotate_and_replace_settings_file(settings_path: &std::path::Path, contents: &[u8]) -> Result<(), SettingsError>
+{
+	if std::fs::read(settings_path).map(|current| current == contents).unwrap_or(false)
+	{
+		return Ok(()); // Nothing changed, don't push out a backup
+	}
+
+	if settings_path.exists()
+	{
+		// Shift settings.json.1 -> .2 and so on, dropping the oldest
+		for number in (1..SETTINGS_BACKUP_COUNT).rev()
+		{
+			let from = backup_path(settings_path, number);
+			if from.exists()
 			{
-				"tank": 0,
-				"damage": 0,
-				"support": 0
+				let to = backup_path(settings_path, number + 1);
+				std::fs::rename(&from, &to).map_err(SettingsError::io("rename", &from))?;
 			}
 		}
-		*/
-		result.battle_pass_level = settings["battle_pass"]["level"].as_u64().expect("Could not parse battle pass level") as u8;
-		if let Some(battle_pass_target) = settings["battle_pass"]["target"].as_str()
-		{
-			result.battle_pass_target = if battle_pass_target == "prestige" { crate::seasons::PRESTIGE_BATTLE_PASS_END } else { crate::seasons::MYTHIC_BATTLE_PASS_END };
-		}
-		else
+		// Copy rather than rename so there is always a settings.json
+		let newest_backup = backup_path(settings_path, 1);
+		std::fs::copy(settings_path, &newest_backup).map_err(SettingsError::io("back up", settings_path))?;
+	}
+
+	write_file_atomically(settings_path, contents)
+}
+
+/// Writes to a temporary file and renames it over `path`, so a crash mid-write never leaves a broken file
+pub fn write_file_atomically(path: &std::path::Path, contents: &[u8]) -> Result<(), SettingsError>
+{
+	use std::io::Write;
+
+	let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+	temp_name.push(".tmp");
+	let temp_path = path.with_file_name(temp_name);
+	let mut file = std::fs::File::create(&temp_path).map_err(SettingsError::io("create", &temp_path))?;
+	file.write_all(contents).map_err(SettingsError::io("write", &temp_path))?;
+	file.sync_all().map_err(SettingsError::io("sync", &temp_path))?;
+	drop(file);
+
+	std::fs::rename(&temp_path, path).map_err(SettingsError::io("replace", path))?;
+	// Make sure the rename itself reaches the disk
+	#[cfg(unix)]
+	if let Some(dir) = path.parent()
+	{
+		std::fs::File::open(dir)
+			.and_then(|dir| dir.sync_all())
+			.map_err(SettingsError::io("sync", dir))?;
+	}
+	Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsBackup
+{
+	pub number: usize,
+	pub path: std::path::PathBuf,
+	pub modified: Option<chrono::DateTime<chrono::Local>>,
+}
+
+/// The rolling backups that currently exist, newest first
+pub fn list_settings_backups(profile: &str) -> Vec<SettingsBackup>
+{
+	let settings_path = match get_settings_path(profile)
+	{
+		Ok(settings_path) => settings_path,
+		Err(_) => return Vec::new(),
+	};
+	(1..=SETTINGS_BACKUP_COUNT)
+		.map(|number| (number, backup_path(&settings_path, number)))
+		.filter(|(_, path)| path.exists())
+		.map(|(number, path)|
 		{
-			// Else assume its a custom value
-			result.battle_pass_target = settings["battle_pass"]["target"].as_u64().expect("Could not parse battle pass target") as u8;
-		}
+			let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok().map(chrono::DateTime::from);
+			SettingsBackup { number, path, modified }
+		})
+		.collect()
+}
 
-		result.tank_wins = settings["roll_mastery"]["tank"].as_u64().expect("Could not parse tank wins") as u8;
-		result.damage_wins = settings["roll_mastery"]["damage"].as_u64().expect("Could not parse damage wins") as u8;
-		result.support_wins = settings["roll_mastery"]["support"].as_u64().expect("Could not parse support wins") as u8;
+fn parse_settings(path: &std::path::Path, contents: &[u8]) -> Result<OverHelperSettings, SettingsError>
+{
+	let settings: serde_json::Value = serde_json::from_slice(contents)
+		.map_err(|source| SettingsError::Parse { path: path.to_path_buf(), source })?;
+	serde_json::from_value(migrate(settings)).map_err(|source| SettingsError::Parse { path: path.to_path_buf(), source })
+}
 
-		result
+/// Reads the settings file, creating it with defaults if it doesn't exist
+fn load_settings_file(settings_path: &std::path::Path) -> Result<OverHelperSettings, SettingsError>
+{
+	if settings_path.exists()
+	{
+		read_settings_file(settings_path)
 	}
 	else
 	{
 		let settings = OverHelperSettings::default();
 		// Create file
-		write_settings(&settings);
-		settings
+		write_settings_file(settings_path, &settings)?;
+		Ok(settings)
 	}
 }
 
+/// Reads the settings file without changing anything on disk, defaults when there is none
+pub fn read_settings_file(settings_path: &std::path::Path) -> Result<OverHelperSettings, SettingsError>
+{
+	if !settings_path.exists()
+	{
+		return Ok(OverHelperSettings::default());
+	}
+	let contents = std::fs::read(settings_path).map_err(SettingsError::io("open", settings_path))?;
+	parse_settings(settings_path, &contents)
+}
+
+/// Loads the settings, falling back to defaults and a notice for the user if they can't be read.
+/// A corrupt file is moved to settings.json.corrupt-<timestamp> so it can be fixed and restored later.
+pub fn load_settings_or_default(profile: &str) -> (OverHelperSettings, Option<SettingsNotice>)
+{
+	match get_settings_path(profile)
+	{
+		Ok(settings_path) => load_settings_file_or_default(&settings_path),
+		Err(error) => (OverHelperSettings::default(), Some(recover_from(error))),
+	}
+}
+
+// Same as load_settings_or_default, for a settings file at a known path
+pub fn load_settings_file_or_default(settings_path: &std::path::Path) -> (OverHelperSettings, Option<SettingsNotice>)
+{
+	match load_settings_file(settings_path)
+	{
+		Ok(settings) => (settings, None),
+		Err(error) => (OverHelperSettings::default(), Some(recover_from(error))),
+	}
+}
+
+/// Notice for settings that couldn't be loaded, moving a corrupt file out of the way
+fn recover_from(error: SettingsError) -> SettingsNotice
+{
+	match &error
+	{
+		SettingsError::Parse { path, .. } =>
+		{
+			match move_aside(path)
+			{
+				Ok(backup_path) => SettingsNotice
+				{
+					message: format!("{}. Your settings have been reset, the old file was kept as {}", error, backup_path.display()),
+					backup_path: Some(backup_path),
+				},
+				Err(rename_error) => SettingsNotice
+				{
+					message: format!("{}. It could not be backed up: {}", error, rename_error),
+					backup_path: None,
+				},
+			}
+		},
+		_ => SettingsNotice { message: format!("{}. Using default settings", error), backup_path: None },
+	}
+}
+
+/// Renames a file that can't be read to <name>.corrupt-<timestamp>, so it can be fixed by hand and nothing overwrites it
+pub fn move_aside(path: &std::path::Path) -> std::io::Result<std::path::PathBuf>
+{
+	let mut corrupt_name = path.file_name().unwrap_or_default().to_os_string();
+	corrupt_name.push(format!(".corrupt-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+	let corrupt_path = path.with_file_name(corrupt_name);
+	std::fs::rename(path, &corrupt_path)?;
+	Ok(corrupt_path)
+}
+
+/// Puts a backed up settings file back in place and loads it, the current file becomes the newest backup.
+/// Nothing is replaced if the backup can't be parsed.
+pub fn restore_settings_backup(profile: &str, backup_path: &std::path::Path) -> Result<OverHelperSettings, SettingsError>
+{
+	restore_settings_file(&get_settings_path(profile)?, backup_path)
+}
+
+fn restore_settings_file(settings_path: &std::path::Path, backup_path: &std::path::Path) -> Result<OverHelperSettings, SettingsError>
+{
+	let contents = std::fs::read(backup_path).map_err(SettingsError::io("open", backup_path))?;
+	let settings = parse_settings(backup_path, &contents)?;
+	replace_settings_file(settings_path, &contents)?;
+	Ok(settings)
+}
+
+/// Opens a file with the system's default application
+pub fn open_in_system(path: &std::path::Path) -> Result<(), SettingsError>
+{
+	let opener = if cfg!(target_os = "windows") { "explorer" } else if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+	std::process::Command::new(opener)
+		.arg(path)
+		.spawn()
+		.map(|_| ())
+		.map_err(SettingsError::io("open", path))
+}
+
 
 
 #[derive(Debug, Clone)]
 pub struct SettingsPage
 {
-	pub battle_pass_custom_target: u8,
+	pub target: seasons::BattlePassTarget, // Kept as stored until the target is edited, a custom level may be past this season's pass
+	pub battle_pass_custom_target: seasons::Level, // Last valid target level
+	pub battle_pass_custom_target_input: String,
+	pub battle_pass_custom_target_error: Option<String>,
 	pub battle_pass_target: seasons::BattlePassTargets,
-	pub theme: iced::Theme
+	pub deadline: Option<chrono::DateTime<chrono::Utc>>, // Last valid deadline
+	pub deadline_input: String,
+	pub deadline_error: Option<String>,
+	pub theme: iced::Theme,
+	pub pace_estimator: seasons::PaceEstimator,
+	pub region: crate::clock::Region,
+	pub premium: bool,
+	pub boost_windows: Vec<seasons::BoostWindow>,
+	pub boost_start_input: String,
+	pub boost_end_input: String,
+	pub boost_multiplier_input: String,
+	pub boost_error: Option<String>,
+	pub play_schedule: seasons::PlaySchedule,
+	pub day_off_input: String,
+	pub day_off_error: Option<String>,
+	pub backups: Vec<SettingsBackup>,
+	pub restore_error: Option<String>,
+	pub profiles: crate::profiles::Profiles, // Changed on disk right away, not when applying
+	pub profile_name_input: String,
+	pub profile_error: Option<String>,
+	pub profile_to_delete: Option<String>, // Waiting for confirmation
+	pub season: seasons::Season, // Season the target levels are shown for
 }
 
 #[derive(Debug, Clone)]
@@ -187,20 +691,42 @@ pub enum Message
 {
 	BattlePassTargetChanged(seasons::BattlePassTargets),
 	BattlePassCustomLevelChanged(String),
+	DeadlineChanged(String),
 	ThemeChanged(SupportedThemes),
+	PaceEstimatorChanged(seasons::PaceEstimator),
+	RegionChanged(crate::clock::Region),
+	PremiumToggled(bool),
+	BoostStartChanged(String),
+	BoostEndChanged(String),
+	BoostMultiplierChanged(String),
+	AddBoostWindow,
+	RemoveBoostWindow(usize),
+	PlayDayToggled(chrono::Weekday, bool),
+	DayOffChanged(String),
+	AddDayOff,
+	RemoveDayOff(usize),
+	ResetToDefaults,
+	RestoreBackup(std::path::PathBuf), // Handled by the app, which owns the loaded settings
+	ProfileNameChanged(String),
+	// Handled by the app, which has to save or reload the profile in use
+	CreateProfile,
+	RenameProfile(String),
+	ConfirmDeleteProfile(String),
+	DeleteProfile(String), // Asks for confirmation first
 }
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SupportedThemes
 {
 	Dark,
 	Light,
 }
 
-impl Into<iced::Theme> for SupportedThemes
+impl From<SupportedThemes> for iced::Theme
 {
-	fn into(self) -> iced::Theme
+	fn from(theme: SupportedThemes) -> Self
 	{
-		match self
+		match theme
 		{
 			SupportedThemes::Dark => iced::Theme::Dark,
 			SupportedThemes::Light => iced::Theme::Light,
@@ -208,6 +734,18 @@ impl Into<iced::Theme> for SupportedThemes
 	}
 }
 
+impl From<&iced::Theme> for SupportedThemes
+{
+	fn from(theme: &iced::Theme) -> Self
+	{
+		match theme
+		{
+			iced::Theme::Light => SupportedThemes::Light,
+			_ => SupportedThemes::Dark,
+		}
+	}
+}
+
 const SUPPORTED_THEMES: [SupportedThemes; 2] = [SupportedThemes::Dark, SupportedThemes::Light];
 
 impl std::fmt::Display for SupportedThemes
@@ -222,26 +760,96 @@ impl std::fmt::Display for SupportedThemes
 	}
 }
 
+fn heading(text: &str) -> iced::widget::Text<'_>
+{
+	iced::widget::Text::new(text)
+		.size(32)
+		.horizontal_alignment(iced::alignment::Horizontal::Center)
+		.vertical_alignment(iced::alignment::Vertical::Center)
+		.width(iced::Length::Fill)
+}
+
 impl SettingsPage
 {
-	pub fn new() -> Self
+	/// Page for editing the given settings, nothing changes until they are applied.
+	/// Targets are shown as levels of the given season's battle pass
+	pub fn from_settings(settings: &OverHelperSettings, season: seasons::Season, profiles: &crate::profiles::Profiles) -> Self
 	{
-		let settings = crate::settings::load_settings_or_default();
 		Self
 		{
-			battle_pass_custom_target: settings.battle_pass_target,
-			battle_pass_target:
-				if settings.battle_pass_target == crate::seasons::PRESTIGE_BATTLE_PASS_END
-					{ seasons::BattlePassTargets::Prestige }
-				else if settings.battle_pass_target == crate::seasons::MYTHIC_BATTLE_PASS_END
-					{ seasons::BattlePassTargets::Mythic }
-				else
-					{ seasons::BattlePassTargets::Custom },
-			theme: settings.theme
+			target: settings.battle_pass.target,
+			battle_pass_custom_target: settings.battle_pass.target.level(&season),
+			battle_pass_custom_target_input: match settings.battle_pass.target
+			{
+				seasons::BattlePassTarget::Custom { level } => level.to_string(),
+				target => target.level(&season).to_string(),
+			},
+			battle_pass_custom_target_error: None,
+			battle_pass_target: settings.battle_pass.target.kind(),
+			deadline: settings.battle_pass.deadline,
+			deadline_input: settings.battle_pass.deadline.map(|deadline| settings.region.format(deadline, "%Y-%m-%d")).unwrap_or_default(),
+			deadline_error: None,
+			theme: settings.theme.into(),
+			pace_estimator: settings.pace_estimator,
+			region: settings.region,
+			premium: settings.battle_pass.premium,
+			boost_windows: settings.boost_windows.clone(),
+			boost_start_input: String::new(),
+			boost_end_input: String::new(),
+			boost_multiplier_input: String::from("2"),
+			boost_error: None,
+			play_schedule: settings.play_schedule.clone(),
+			day_off_input: String::new(),
+			day_off_error: None,
+			backups: list_settings_backups(&profiles.active),
+			restore_error: None,
+			profiles: profiles.clone(),
+			profile_name_input: String::new(),
+			profile_error: None,
+			profile_to_delete: None,
+			season,
 		}
 	}
 
-	pub fn view(&self) -> iced::Element<Message>
+	/// Like boost windows, the deadline is at the reset on that day, counted in the selected region
+	fn parse_deadline(&mut self)
+	{
+		if self.deadline_input.trim().is_empty()
+		{
+			self.deadline = None;
+			self.deadline_error = None;
+		}
+		else
+		{
+			match seasons::parse_local_date(self.deadline_input.trim(), seasons::SEASON_CALENDAR.reset_hour(), self.region)
+			{
+				Some(parsed) =>
+				{
+					self.deadline = Some(parsed);
+					self.deadline_error = None;
+				},
+				None => self.deadline_error = Some(String::from("Enter the deadline as YYYY-MM-DD, or leave it empty")),
+			}
+		}
+	}
+
+	pub fn battle_pass_target(&self) -> seasons::BattlePassTarget
+	{
+		self.target
+	}
+
+	/// Target picked on the page, once the user changes it
+	fn edited_target(&self) -> seasons::BattlePassTarget
+	{
+		match self.battle_pass_target
+		{
+			seasons::BattlePassTargets::Mythic => seasons::BattlePassTarget::Mythic,
+			seasons::BattlePassTargets::Prestige => seasons::BattlePassTarget::Prestige,
+			seasons::BattlePassTargets::Custom => seasons::BattlePassTarget::custom(self.battle_pass_custom_target).unwrap_or_default(),
+		}
+	}
+
+	pub fn view(&self) -> iced::Element<'_, Message>
 	{
 		let current_battle_pass_target = self.battle_pass_target;
 		let battle_pass_target_picker = iced::widget::pick_list::PickList::new
@@ -260,22 +868,26 @@ impl SettingsPage
 		let battle_pass_custom_level_picker: iced::Element<_> = match current_battle_pass_target
 		{
 			seasons::BattlePassTargets::Prestige | seasons::BattlePassTargets::Mythic => iced::widget::Space::with_width(iced::Length::Fill).into(), // Empty space
-			seasons::BattlePassTargets::Custom => iced::widget::TextInput::new("Custom level (ex: 120)", &self.battle_pass_custom_target.to_string(), Message::BattlePassCustomLevelChanged)
-				.width(iced::Length::Fill)
-				.size(32)
-				.into()
+			seasons::BattlePassTargets::Custom =>
+			{
+				let input = iced::widget::TextInput::new("Custom level (ex: 120)", &self.battle_pass_custom_target_input, Message::BattlePassCustomLevelChanged)
+					.width(iced::Length::Fill)
+					.size(32)
+					;
+				let error = iced::widget::Text::new(self.battle_pass_custom_target_error.clone().unwrap_or_default());
+				iced::widget::Column::new()
+					.push(input)
+					.push(error)
+					.width(iced::Length::Fill)
+					.into()
+			}
 		};
 		let battle_pass_custom_level_picker = iced::widget::Row::new()
 			.push(iced::widget::Space::with_width(iced::Length::FillPortion(2)))
 			.push(battle_pass_custom_level_picker)
 			.push(iced::widget::Space::with_width(iced::Length::FillPortion(2)))
 			;
-		let current_theme = match self.theme
-		{
-			iced::Theme::Dark => SupportedThemes::Dark,
-			iced::Theme::Light => SupportedThemes::Light,
-			_ => unreachable!("Unsupported theme")
-		};
+		let current_theme = SupportedThemes::from(&self.theme);
 		let theme_picker = iced::widget::pick_list::PickList::new
 			(
 				&SUPPORTED_THEMES[..],
@@ -289,29 +901,265 @@ impl SettingsPage
 			.push(theme_picker)
 			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
 			;
-		iced::widget::Column::new()
-			.push
+		let pace_estimator_picker = iced::widget::pick_list::PickList::new
 			(
-				iced::widget::Text::new("Battle Pass Target")
-					.size(32)
-					.horizontal_alignment(iced::alignment::Horizontal::Center)
-					.vertical_alignment(iced::alignment::Vertical::Center)
-					.width(iced::Length::Fill)
+				&seasons::PACE_ESTIMATORS[..],
+				Some(self.pace_estimator),
+				Message::PaceEstimatorChanged
 			)
+			.width(iced::Length::Fill)
+			;
+		let pace_estimator_picker = iced::widget::Row::new()
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			.push(pace_estimator_picker)
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			;
+		let region_picker = iced::widget::pick_list::PickList::new
+			(
+				&crate::clock::REGIONS[..],
+				Some(self.region),
+				Message::RegionChanged
+			)
+			.width(iced::Length::Fill)
+			;
+		let region_picker = iced::widget::Row::new()
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			.push(region_picker)
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(1)))
+			;
+		let deadline_input = iced::widget::Column::new()
+			.push(iced::widget::TextInput::new("Finish by (YYYY-MM-DD, optional)", &self.deadline_input, Message::DeadlineChanged).width(iced::Length::Fill))
+			.push(iced::widget::Text::new(self.deadline_error.clone().unwrap_or_default()))
+			;
+		let deadline_input = iced::widget::Row::new()
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(2)))
+			.push(deadline_input.width(iced::Length::FillPortion(1)))
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(2)))
+			;
+		iced::widget::Column::new()
+			.push(heading("Battle Pass Target"))
 			.push(battle_pass_target_picker)
 			.push(battle_pass_custom_level_picker)
+			.push(deadline_input)
+			.push(heading("Theme"))
+			.push(theme_picker)
+			.push(heading("Pace Projection"))
+			.push(pace_estimator_picker)
+			.push(heading("Timezone"))
+			.push(region_picker)
+			.push(heading("XP Boosts"))
+			.push(self.boosts_view())
+			.push(heading("Play Schedule"))
+			.push(self.schedule_view())
+			.push(heading("Profiles"))
+			.push(self.profiles_view())
+			.push(heading("Backups"))
+			.push(self.backups_view())
 			.push
 			(
-				iced::widget::Text::new("Theme")
-					.size(32)
-					.horizontal_alignment(iced::alignment::Horizontal::Center)
-					.vertical_alignment(iced::alignment::Vertical::Center)
-					.width(iced::Length::Fill)
+				iced::widget::Container::new
+				(
+					iced::widget::Button::new(iced::widget::Text::new("Reset to defaults"))
+						.on_press(Message::ResetToDefaults)
+				)
+				.width(iced::Length::Fill)
+				.padding(16)
+				.center_x()
 			)
-			.push(theme_picker)
 			.into()
 	}
 
+	fn boosts_view(&self) -> iced::Element<'_, Message>
+	{
+		let mut boosts = iced::widget::Column::new()
+			.push(iced::widget::Checkbox::new("I own the premium battle pass", self.premium, Message::PremiumToggled))
+			.spacing(4)
+			.align_items(iced::Alignment::Center)
+			;
+		for (index, window) in self.boost_windows.iter().enumerate()
+		{
+			let remove_button = iced::widget::Button::new(iced::widget::Text::new("Remove"))
+				.on_press(Message::RemoveBoostWindow(index))
+				;
+			boosts = boosts.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::Text::new(format!("{}x XP from {} to {}", window.multiplier, window.start.format("%Y-%m-%d %H:%M"), window.end.format("%Y-%m-%d %H:%M"))))
+					.push(remove_button)
+					.spacing(16)
+					.align_items(iced::Alignment::Center)
+			);
+		}
+		let add_button = iced::widget::Button::new(iced::widget::Text::new("Add"))
+			.on_press(Message::AddBoostWindow)
+			;
+		boosts
+			.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::TextInput::new("Start (YYYY-MM-DD)", &self.boost_start_input, Message::BoostStartChanged).width(iced::Length::FillPortion(2)))
+					.push(iced::widget::TextInput::new("End (YYYY-MM-DD)", &self.boost_end_input, Message::BoostEndChanged).width(iced::Length::FillPortion(2)))
+					.push(iced::widget::TextInput::new("Multiplier", &self.boost_multiplier_input, Message::BoostMultiplierChanged).width(iced::Length::FillPortion(1)))
+					.push(add_button)
+					.spacing(8)
+					.padding([0, 64])
+					.align_items(iced::Alignment::Center)
+			)
+			.push(iced::widget::Text::new(self.boost_error.clone().unwrap_or_default()))
+			.into()
+	}
+
+	/// Boost window from the inputs, the end date is exclusive
+	fn boost_window_from_inputs(&self) -> Result<seasons::BoostWindow, String>
+	{
+		// Events start at the same hour as seasons
+		let reset_hour = seasons::SEASON_CALENDAR.reset_hour();
+		let start = seasons::parse_season_date(self.boost_start_input.trim(), reset_hour).ok_or("Enter the start as YYYY-MM-DD")?;
+		let end = seasons::parse_season_date(self.boost_end_input.trim(), reset_hour).ok_or("Enter the end as YYYY-MM-DD")?;
+		let multiplier = self.boost_multiplier_input.trim().parse::<f64>().map_err(|_| "Enter a multiplier such as 2 or 1.5")?;
+		if end <= start
+		{
+			return Err(String::from("The boost has to end after it starts"));
+		}
+		if !(multiplier > 1.0 && multiplier <= 10.0)
+		{
+			return Err(String::from("The multiplier has to be above 1 and at most 10"));
+		}
+		Ok(seasons::BoostWindow { start, end, multiplier })
+	}
+
+	fn schedule_view(&self) -> iced::Element<'_, Message>
+	{
+		let weekdays = seasons::WEEKDAYS.iter().fold(iced::widget::Row::new().spacing(16), |row, weekday|
+		{
+			let weekday = *weekday;
+			row.push(iced::widget::Checkbox::new(weekday.to_string(), self.play_schedule.weekdays.contains(&weekday), move |checked| Message::PlayDayToggled(weekday, checked)))
+		});
+		let mut schedule = iced::widget::Column::new()
+			.push(weekdays)
+			.spacing(4)
+			.align_items(iced::Alignment::Center)
+			;
+		for (index, day_off) in self.play_schedule.days_off.iter().enumerate()
+		{
+			let remove_button = iced::widget::Button::new(iced::widget::Text::new("Remove"))
+				.on_press(Message::RemoveDayOff(index))
+				;
+			schedule = schedule.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::Text::new(format!("Day off on {}", day_off.format("%Y-%m-%d"))))
+					.push(remove_button)
+					.spacing(16)
+					.align_items(iced::Alignment::Center)
+			);
+		}
+		let add_button = iced::widget::Button::new(iced::widget::Text::new("Add day off"))
+			.on_press(Message::AddDayOff)
+			;
+		schedule
+			.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::TextInput::new("Day off (YYYY-MM-DD)", &self.day_off_input, Message::DayOffChanged).width(iced::Length::FillPortion(2)))
+					.push(add_button)
+					.spacing(8)
+					.padding([0, 64])
+					.align_items(iced::Alignment::Center)
+			)
+			.push(iced::widget::Text::new(self.day_off_error.clone().unwrap_or_default()))
+			.into()
+	}
+
+	fn profiles_view(&self) -> iced::Element<'_, Message>
+	{
+		let mut profiles = iced::widget::Column::new()
+			.spacing(4)
+			.align_items(iced::Alignment::Center)
+			;
+		for name in &self.profiles.names
+		{
+			let rename_button = iced::widget::Button::new(iced::widget::Text::new("Rename"))
+				.on_press(Message::RenameProfile(name.clone()))
+				;
+			let mut row = iced::widget::Row::new()
+				.spacing(16)
+				.align_items(iced::Alignment::Center)
+				;
+			if *name == self.profiles.active
+			{
+				row = row.push(iced::widget::Text::new(format!("{} (in use)", name))).push(rename_button);
+			}
+			else
+			{
+				// Deleting takes the history with it, so it needs a second click
+				let delete_button = if self.profile_to_delete.as_ref() == Some(name)
+				{
+					iced::widget::Button::new(iced::widget::Text::new("Really delete, history included?"))
+						.on_press(Message::ConfirmDeleteProfile(name.clone()))
+				}
+				else
+				{
+					iced::widget::Button::new(iced::widget::Text::new("Delete"))
+						.on_press(Message::DeleteProfile(name.clone()))
+				};
+				row = row.push(iced::widget::Text::new(name)).push(rename_button).push(delete_button);
+			}
+			profiles = profiles.push(row);
+		}
+		let add_button = iced::widget::Button::new(iced::widget::Text::new("Add"))
+			.on_press(Message::CreateProfile)
+			;
+		profiles
+			.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::TextInput::new("Name for a new or renamed profile", &self.profile_name_input, Message::ProfileNameChanged).width(iced::Length::FillPortion(2)))
+					.push(add_button)
+					.spacing(8)
+					.padding([0, 64])
+					.align_items(iced::Alignment::Center)
+			)
+			.push(iced::widget::Text::new(self.profile_error.clone().unwrap_or_default()))
+			.into()
+	}
+
+	fn backups_view(&self) -> iced::Element<'_, Message>
+	{
+		let mut backups = iced::widget::Column::new()
+			.spacing(4)
+			.align_items(iced::Alignment::Center)
+			;
+		if self.backups.is_empty()
+		{
+			backups = backups.push(iced::widget::Text::new("No backups yet, one is kept each time the settings are saved"));
+		}
+		for backup in &self.backups
+		{
+			let modified = match backup.modified
+			{
+				Some(modified) => modified.format("%Y-%m-%d %H:%M").to_string(),
+				None => String::from("unknown date"),
+			};
+			let restore_button = iced::widget::Button::new(iced::widget::Text::new("Restore"))
+				.on_press(Message::RestoreBackup(backup.path.clone()))
+				;
+			backups = backups.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::Text::new(format!("Backup {} ({})", backup.number, modified)))
+					.push(restore_button)
+					.spacing(16)
+					.align_items(iced::Alignment::Center)
+			);
+		}
+		if let Some(restore_error) = &self.restore_error
+		{
+			backups = backups.push(iced::widget::Text::new(restore_error));
+		}
+		backups.into()
+	}
+
 	pub fn update(&mut self, message: Message)
 	{
 		match message
@@ -320,18 +1168,285 @@ impl SettingsPage
 			{
 				match battle_pass_target
 				{
-					seasons::BattlePassTargets::Prestige => self.battle_pass_custom_target = crate::seasons::PRESTIGE_BATTLE_PASS_END,
-					seasons::BattlePassTargets::Mythic => self.battle_pass_custom_target = crate::seasons::MYTHIC_BATTLE_PASS_END,
+					seasons::BattlePassTargets::Prestige => self.battle_pass_custom_target = self.season.battle_pass_levels,
+					seasons::BattlePassTargets::Mythic => self.battle_pass_custom_target = self.season.mythic_level,
 					seasons::BattlePassTargets::Custom => ()
 				}
+				self.battle_pass_custom_target_input = self.battle_pass_custom_target.to_string();
+				self.battle_pass_custom_target_error = None;
 				self.battle_pass_target = battle_pass_target;
+				self.target = self.edited_target();
+			},
+			Message::BattlePassCustomLevelChanged(battle_pass_target) =>
+			{
+				// Keep the last valid level until the input is valid again
+				let target = battle_pass_target.trim().parse::<seasons::Level>()
+					.ok()
+					.filter(|level| (1..=self.season.battle_pass_levels).contains(level))
+					.ok_or_else(|| format!("Enter a level between 1 and {}", self.season.battle_pass_levels));
+				match target
+				{
+					Ok(target) =>
+					{
+						self.battle_pass_custom_target = target;
+						self.battle_pass_custom_target_error = None;
+						self.target = self.edited_target();
+					},
+					Err(error) => self.battle_pass_custom_target_error = Some(error),
+				}
+				self.battle_pass_custom_target_input = battle_pass_target;
+			},
+			Message::DeadlineChanged(deadline) =>
+			{
+				self.deadline_input = deadline;
+				self.parse_deadline();
 			},
-			Message::BattlePassCustomLevelChanged(battle_pass_target) => self.battle_pass_custom_target = battle_pass_target.parse().unwrap_or_else(|e| {eprintln!("Invalid battle pass target: {}", e); 0}),
 			Message::ThemeChanged(theme) => self.theme = match theme
 			{
 				SupportedThemes::Dark => iced::Theme::Dark,
 				SupportedThemes::Light => iced::Theme::Light
-			}
+			},
+			Message::PaceEstimatorChanged(pace_estimator) => self.pace_estimator = pace_estimator,
+			Message::RegionChanged(region) =>
+			{
+				self.region = region;
+				self.parse_deadline(); // The typed date now falls on a different reset
+			},
+			Message::PremiumToggled(premium) => self.premium = premium,
+			Message::BoostStartChanged(start) => self.boost_start_input = start,
+			Message::BoostEndChanged(end) => self.boost_end_input = end,
+			Message::BoostMultiplierChanged(multiplier) => self.boost_multiplier_input = multiplier,
+			Message::AddBoostWindow =>
+			{
+				match self.boost_window_from_inputs()
+				{
+					Ok(window) =>
+					{
+						self.boost_windows.push(window);
+						self.boost_windows.sort_by_key(|window| window.start);
+						self.boost_start_input.clear();
+						self.boost_end_input.clear();
+						self.boost_error = None;
+					},
+					Err(error) => self.boost_error = Some(error),
+				}
+			},
+			Message::RemoveBoostWindow(index) =>
+			{
+				if index < self.boost_windows.len()
+				{
+					self.boost_windows.remove(index);
+				}
+			},
+			Message::PlayDayToggled(weekday, checked) =>
+			{
+				self.play_schedule.weekdays.retain(|day| *day != weekday);
+				if checked
+				{
+					self.play_schedule.weekdays.push(weekday);
+					self.play_schedule.weekdays.sort_by_key(|day| day.num_days_from_monday());
+				}
+			},
+			Message::DayOffChanged(day_off) => self.day_off_input = day_off,
+			Message::AddDayOff =>
+			{
+				// Days off are calendar days in the picked timezone, no reset hour involved
+				match chrono::NaiveDate::parse_from_str(self.day_off_input.trim(), "%Y-%m-%d")
+				{
+					Ok(day_off) =>
+					{
+						if !self.play_schedule.days_off.contains(&day_off)
+						{
+							self.play_schedule.days_off.push(day_off);
+							self.play_schedule.days_off.sort();
+						}
+						self.day_off_input.clear();
+						self.day_off_error = None;
+					},
+					Err(_) => self.day_off_error = Some(String::from("Enter the day off as YYYY-MM-DD")),
+				}
+			},
+			Message::RemoveDayOff(index) =>
+			{
+				if index < self.play_schedule.days_off.len()
+				{
+					self.play_schedule.days_off.remove(index);
+				}
+			},
+			Message::ResetToDefaults =>
+			{
+				let defaults = Self::from_settings(&OverHelperSettings::default(), self.season.clone(), &self.profiles);
+				*self = Self
+				{
+					backups: std::mem::take(&mut self.backups),
+					profile_name_input: std::mem::take(&mut self.profile_name_input),
+					..defaults
+				};
+			},
+			Message::ProfileNameChanged(name) => self.profile_name_input = name,
+			Message::DeleteProfile(name) => self.profile_to_delete = Some(name),
+			Message::RestoreBackup(_) | Message::CreateProfile | Message::RenameProfile(_) | Message::ConfirmDeleteProfile(_) => (),
+		}
+	}
+}
+
+/// Empty directory for tests that touch files, left over from a failed run or not
+#[cfg(test)]
+pub fn test_dir(name: &str) -> std::path::PathBuf
+{
+	let dir = std::env::temp_dir().join(format!("overhelper-{}-{}", name, std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use chrono::TimeZone;
+
+	fn load_from_str(json: &str) -> OverHelperSettings
+	{
+		serde_json::from_value(migrate(serde_json::from_str(json).unwrap())).unwrap()
+	}
+
+	#[test]
+	fn unversioned_file_is_migrated()
+	{
+		let settings = load_from_str(r#"
+		{
+			"battle_pass": { "level": 42, "target": "mythic" },
+			"roll_mastery": { "tank": 1, "damage": 2, "support": 3 }
+		}"#);
+		assert_eq!(settings.version, SETTINGS_VERSION);
+		assert_eq!(settings.battle_pass.level, 42);
+		assert_eq!(settings.battle_pass.target, seasons::BattlePassTarget::Mythic);
+		assert_eq!(settings.roll_mastery, RollMasterySettings { tank: 1, damage: 2, support: 3, ..RollMasterySettings::default() });
+
+		// Custom targets used to be written as strings
+		assert_eq!(load_from_str(r#"{ "battle_pass": { "level": 0, "target": "120" } }"#).battle_pass.target, seasons::BattlePassTarget::Custom { level: 120 });
+		assert_eq!(load_from_str(r#"{ "battle_pass": { "level": 0, "target": 200 } }"#).battle_pass.target, seasons::BattlePassTarget::Prestige);
+	}
+
+	#[test]
+	fn custom_target_is_validated()
+	{
+		let target = seasons::BattlePassTarget::custom(120).unwrap();
+		assert_eq!(serde_json::to_value(target).unwrap(), serde_json::json!({ "kind": "custom", "level": 120 }));
+
+		let load = |json: &str| parse_settings(std::path::Path::new("settings.json"), json.as_bytes());
+		assert_eq!(load(r#"{ "version": 2, "battle_pass": { "target": { "kind": "custom", "level": 120 } } }"#).unwrap().battle_pass.target, target);
+		// Levels outside the battle pass fall back to the default target, keeping the rest of the file
+		for json in
+		[
+			r#"{ "version": 2, "battle_pass": { "level": 57, "target": { "kind": "custom", "level": 0 } } }"#,
+			r#"{ "version": 2, "battle_pass": { "level": 57, "target": { "kind": "custom", "level": 201 } } }"#,
+			r#"{ "version": 1, "battle_pass": { "level": 57, "target": 0 } }"#, // Old files could hold a target of 0
+			r#"{ "version": 1, "battle_pass": { "level": 57, "target": 250 } }"#,
+		]
+		{
+			let settings = load(json).unwrap();
+			assert_eq!((settings.battle_pass.level, settings.battle_pass.target), (57, seasons::BattlePassTarget::default()), "{}", json);
 		}
+		assert!(seasons::BattlePassTarget::custom(0).is_err());
+		assert!(seasons::BattlePassTarget::custom(300).is_err());
+		// A target that isn't a level at all is still a broken file
+		assert!(load(r#"{ "version": 2, "battle_pass": { "target": { "kind": "custom", "level": "high" } } }"#).is_err());
+	}
+
+	#[test]
+	fn every_field_round_trips()
+	{
+		let settings = OverHelperSettings
+		{
+			version: SETTINGS_VERSION,
+			battle_pass: BattlePassSettings { level: 57, xp: 4_200, target: seasons::BattlePassTarget::Custom { level: 120 }, season: Some(4), premium: true, deadline: Some(chrono::Utc.with_ymd_and_hms(2023, 1, 20, 18, 0, 0).unwrap()) },
+			boost_windows: vec![seasons::BoostWindow { start: chrono::Utc.with_ymd_and_hms(2023, 1, 13, 18, 0, 0).unwrap(), end: chrono::Utc.with_ymd_and_hms(2023, 1, 16, 18, 0, 0).unwrap(), multiplier: 2.0 }],
+			play_schedule: seasons::PlaySchedule { weekdays: vec![chrono::Weekday::Sat, chrono::Weekday::Sun], days_off: vec![chrono::NaiveDate::from_ymd_opt(2022, 12, 25).unwrap()] },
+			season_archive: vec![seasons::ArchivedSeason { season: 3, level: 80, target: 80 }],
+			roll_mastery: RollMasterySettings { tank: 1, damage: 2, support: 3, tank_goal: 10, damage_goal: 20, support_goal: 30 },
+			planner: PlannerSettings { daily_challenge_xp: 1, weekly_challenge_xp: 2, win_xp: 3, loss_xp: 4, win_rate: 5, premium_boost: 6, xp_per_hour: 7, max_hours_per_day: 8 },
+			theme: SupportedThemes::Light,
+			pace_estimator: seasons::PaceEstimator::ExponentiallyWeighted,
+			region: crate::clock::Region::Zone(chrono_tz::Europe::Paris),
+		};
+		// Destructured without `..` so a new field has to be added here, with a non-default value
+		let OverHelperSettings { version: _, battle_pass, boost_windows, play_schedule, season_archive, roll_mastery, planner, theme, pace_estimator, region } = &settings;
+		let default = OverHelperSettings::default();
+		assert_ne!(*battle_pass, default.battle_pass);
+		assert_ne!(*boost_windows, default.boost_windows);
+		assert_ne!(*play_schedule, default.play_schedule);
+		assert_ne!(*season_archive, default.season_archive);
+		assert_ne!(*roll_mastery, default.roll_mastery);
+		assert_ne!(*planner, default.planner);
+		assert_ne!(*theme, default.theme);
+		assert_ne!(*pace_estimator, default.pace_estimator);
+		assert_ne!(*region, default.region);
+
+		let contents = serde_json::to_vec_pretty(&settings).unwrap();
+		assert_eq!(parse_settings(std::path::Path::new("settings.json"), &contents).unwrap(), settings);
+
+		// A day inside the stored season, so nothing is clamped to another season
+		let clock: std::sync::Arc<dyn crate::clock::Clock> = std::sync::Arc::new(crate::clock::FixedClock(chrono::Utc.with_ymd_and_hms(2023, 5, 1, 12, 0, 0).unwrap()));
+		assert_eq!(appstate_to_settings(&settings_to_appstate(&settings, clock)), settings);
+	}
+
+	#[test]
+	fn replacing_settings_keeps_rolling_backups()
+	{
+		let dir = test_dir("backups");
+		let settings_path = dir.join("settings.json");
+		for save in 0..=SETTINGS_BACKUP_COUNT + 1
+		{
+			rotate_and_replace_settings_file(&settings_path, save.to_string().as_bytes()).unwrap();
+		}
+		rotate_and_replace_settings_file(&settings_path, b"6").unwrap(); // Unchanged, no new backup
+
+		assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), "6");
+		for number in 1..=SETTINGS_BACKUP_COUNT
+		{
+			assert_eq!(std::fs::read_to_string(backup_path(&settings_path, number)).unwrap(), (6 - number).to_string());
+		}
+		assert!(!backup_path(&settings_path, SETTINGS_BACKUP_COUNT + 1).exists());
+		assert!(!settings_path.with_file_name("settings.json.tmp").exists());
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn corrupt_settings_are_moved_aside_and_restorable()
+	{
+		let dir = test_dir("corrupt");
+		let settings_path = dir.join("settings.json");
+		std::fs::write(&settings_path, r#"{ "version": 2, "battle_pass": { "level": 4"#).unwrap(); // Cut off mid-write
+
+		let (settings, notice) = load_settings_file_or_default(&settings_path);
+		assert_eq!(settings, OverHelperSettings::default());
+		let backup = notice.unwrap().backup_path.unwrap();
+		assert!(backup.file_name().unwrap().to_string_lossy().starts_with("settings.json.corrupt-"));
+		assert!(!settings_path.exists());
+		assert!(std::fs::read_to_string(&backup).unwrap().ends_with(r#""level": 4"#));
+
+		// A backup that can't be parsed is not put back
+		assert!(matches!(restore_settings_file(&settings_path, &backup), Err(SettingsError::Parse { .. })));
+		assert!(!settings_path.exists());
+
+		std::fs::write(&backup, r#"{ "version": 2, "battle_pass": { "level": 42 } }"#).unwrap(); // Fixed by hand
+		assert_eq!(restore_settings_file(&settings_path, &backup).unwrap().battle_pass.level, 42);
+		// Debug builds only restore into the running app
+		assert_eq!(settings_path.exists(), SAVES_FILES);
+		let (settings, notice) = load_settings_file_or_default(&settings_path);
+		assert_eq!(settings.battle_pass.level, if SAVES_FILES { 42 } else { 0 });
+		assert!(notice.is_none());
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn missing_fields_use_defaults()
+	{
+		let settings = load_from_str(r#"{ "version": 2, "battle_pass": { "level": 7 } }"#);
+		assert_eq!(settings.battle_pass.level, 7);
+		assert_eq!(settings.battle_pass.target, seasons::BattlePassTarget::Prestige);
+		assert_eq!(settings.roll_mastery, RollMasterySettings::default());
 	}
 }
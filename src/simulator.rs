@@ -0,0 +1,210 @@
+use chrono::Datelike;
+
+use crate::clock::{Clock, FixedClock, Region};
+use crate::seasons;
+
+/// A made up week of play, to see what skipping days or catching up does to the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simulator
+{
+	pub skipped_days: u32, // Days from today without any play
+	pub xp_per_weekday: [u32; 7], // Monday first
+}
+
+impl Default for Simulator
+{
+	fn default() -> Self
+	{
+		Self { skipped_days: 0, xp_per_weekday: [seasons::XP_PER_LEVEL; 7] }
+	}
+}
+
+/// One day of the simulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedDay
+{
+	pub date: chrono::NaiveDate,
+	pub xp_gained: u32,
+	pub xp_required_per_day: f64, // As it looks before the day is played
+	pub progress: seasons::BattlePassProgress, // After the day is played
+}
+
+impl Simulator
+{
+	pub fn xp_on(&self, weekday: chrono::Weekday) -> u32
+	{
+		self.xp_per_weekday[weekday.num_days_from_monday() as usize]
+	}
+
+	/// Plays the plan from now until the goal end, today being the first day
+	pub fn simulate(&self, progress: seasons::BattlePassProgress, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> Vec<SimulatedDay>
+	{
+		let goal_end = seasons::get_goal_end(clock, deadline);
+		let mut days = Vec::new();
+		let mut progress = progress;
+		let mut date = region.local_date(clock.now());
+		let mut start = clock.now();
+		while start < goal_end
+		{
+			// Same pacing as the tracker would show at the start of that day
+			let xp_required_per_day = seasons::get_xp_required_per_day(progress, &FixedClock(start), deadline);
+			let xp_gained = if (days.len() as u32) < self.skipped_days { 0 } else { self.xp_on(date.weekday()) };
+			progress = progress.add_xp(xp_gained);
+			days.push(SimulatedDay { date, xp_gained, xp_required_per_day, progress });
+			date = date.succ_opt().unwrap();
+			start = region.start_of_day(date);
+		}
+		days
+	}
+
+	pub fn update(&mut self, message: Message)
+	{
+		// Empty inputs count as 0, anything else that isn't a number is ignored
+		let parse = |value: &str| if value.is_empty() { Some(0) } else { value.parse::<u32>().ok() };
+		match message
+		{
+			Message::SkippedDaysChanged(value) =>
+			{
+				if let Some(value) = parse(&value)
+				{
+					self.skipped_days = value;
+				}
+			},
+			Message::WeekdayXpChanged(weekday, value) =>
+			{
+				if let Some(value) = parse(&value)
+				{
+					self.xp_per_weekday[weekday.num_days_from_monday() as usize] = value;
+				}
+			},
+		}
+	}
+
+	pub fn view(&self, progress: seasons::BattlePassProgress, clock: &dyn Clock, deadline: Option<chrono::DateTime<chrono::Utc>>, region: Region) -> iced::Element<'_, Message>
+	{
+		let skipped_days = iced::widget::Row::new()
+			.push(iced::widget::Text::new("Days to skip from today").size(24).width(iced::Length::FillPortion(2)))
+			.push(iced::widget::TextInput::new("0", &self.skipped_days.to_string(), Message::SkippedDaysChanged).width(iced::Length::FillPortion(1)))
+			.push(iced::widget::Space::with_width(iced::Length::FillPortion(3)))
+			.spacing(8)
+			.align_items(iced::Alignment::Center)
+			;
+		let weekdays = seasons::WEEKDAYS.iter().fold(iced::widget::Row::new().spacing(8), |row, weekday|
+		{
+			let weekday = *weekday;
+			row.push
+			(
+				iced::widget::Column::new()
+					.push(iced::widget::Text::new(format!("{} XP", weekday)))
+					.push(iced::widget::TextInput::new("0", &self.xp_on(weekday).to_string(), move |value| Message::WeekdayXpChanged(weekday, value)))
+					.width(iced::Length::Fill)
+			)
+		});
+
+		let days = self.simulate(progress, clock, deadline, region);
+		let best_day = self.xp_per_weekday.iter().copied().max().unwrap_or(0) as f64;
+		let out_of_reach = days.iter().find(|day| day.xp_required_per_day > best_day);
+		let summary = match (days.iter().find(|day| day.progress.is_complete()), days.last())
+		{
+			_ if progress.is_complete() => String::from(seasons::TARGET_REACHED),
+			(Some(day), _) => format!("With this plan you reach level {} on {}", progress.target(), day.date.format("%B %d")),
+			(None, Some(last)) =>
+			{
+				let out_of_reach = match out_of_reach
+				{
+					Some(day) => format!(", out of reach from {} even on your best day", day.date.format("%B %d")),
+					None => String::new(),
+				};
+				format!("With this plan you end at level {}, {} levels short{}", last.progress.level(), progress.target() - last.progress.level(), out_of_reach)
+			},
+			(None, None) => String::from("There are no days left to simulate"),
+		};
+
+		let mut day_list = iced::widget::Column::new()
+			.spacing(4)
+			;
+		for day in &days
+		{
+			let gained = if day.xp_gained == 0
+			{
+				String::from("no play")
+			}
+			else
+			{
+				format!("+{:.2} levels", day.xp_gained as f64 / seasons::XP_PER_LEVEL as f64)
+			};
+			let reachable = if day.xp_required_per_day > best_day { " (out of reach)" } else { "" };
+			day_list = day_list.push
+			(
+				iced::widget::Row::new()
+					.push(iced::widget::Text::new(day.date.format("%a %b %d").to_string()).width(iced::Length::FillPortion(1)))
+					.push(iced::widget::Text::new(gained).width(iced::Length::FillPortion(1)))
+					.push(iced::widget::Text::new(format!("level {:.2}", day.progress.fractional_level())).width(iced::Length::FillPortion(1)))
+					.push(iced::widget::Text::new(format!("{:.2} levels a day needed{}", day.xp_required_per_day / seasons::XP_PER_LEVEL as f64, reachable)).width(iced::Length::FillPortion(2)))
+					.spacing(8)
+			);
+		}
+
+		iced::widget::Column::new()
+			.push
+			(
+				iced::widget::Text::new("What If")
+					.size(32)
+					.horizontal_alignment(iced::alignment::Horizontal::Center)
+					.width(iced::Length::Fill)
+			)
+			.push(skipped_days)
+			.push(weekdays)
+			.push(iced::widget::Text::new(summary).size(32))
+			.push(iced::widget::Scrollable::new(day_list).height(iced::Length::Fill))
+			.spacing(8)
+			.padding(16)
+			.into()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum Message
+{
+	SkippedDaysChanged(String),
+	WeekdayXpChanged(chrono::Weekday, String),
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use chrono::TimeZone;
+
+	const UTC: Region = Region::Zone(chrono_tz::UTC);
+
+	fn clock() -> FixedClock
+	{
+		FixedClock(chrono::Utc.with_ymd_and_hms(2023, 1, 1, 18, 0, 0).unwrap()) // 37 days left in season 2
+	}
+
+	#[test]
+	fn skipped_days_raise_the_requirement()
+	{
+		let progress = seasons::BattlePassProgress::new(0, 0, 74);
+		let simulator = Simulator { skipped_days: 7, ..Simulator::default() };
+		let days = simulator.simulate(progress, &clock(), None, UTC);
+		assert_eq!(days.len(), 38); // The rest of today and February 7 until the reset count too
+		assert_eq!(days[0].xp_required_per_day, 20_000.0);
+		assert!(days[..7].iter().all(|day| day.xp_gained == 0));
+		assert_eq!(days[7].date, chrono::NaiveDate::from_ymd_opt(2023, 1, 8).unwrap());
+		assert_eq!(days[7].xp_required_per_day, 740_000.0 / 30.75);
+		assert!(!days.last().unwrap().progress.is_complete());
+	}
+
+	#[test]
+	fn catching_up_reaches_the_target()
+	{
+		let progress = seasons::BattlePassProgress::new(0, 0, 74);
+		let simulator = Simulator { skipped_days: 0, xp_per_weekday: [20_000; 7] };
+		let days = simulator.simulate(progress, &clock(), None, UTC);
+		let finished = days.iter().find(|day| day.progress.is_complete()).unwrap();
+		assert_eq!(finished.date, chrono::NaiveDate::from_ymd_opt(2023, 2, 6).unwrap());
+		assert!(days.iter().all(|day| day.xp_required_per_day <= 20_000.0));
+	}
+}